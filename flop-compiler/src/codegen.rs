@@ -0,0 +1,1443 @@
+use crate::{
+    error::CompileError,
+    names::{mangle, Names},
+    source_map::SourceMap,
+};
+use flop_frontend::{
+    ast::{
+        Conditional, FunctionCall, FunctionDefinition, Iteration, Lambda, Logical, Loop, Match,
+        Node, Parameters, Pattern, Recur, VariableDefinition, While, CASE_EQUAL,
+    },
+    stack::Stack,
+    token::{Token, TokenKind},
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+};
+
+/// The builtins the runtime provides, with the Rust function implementing each.
+const BUILTINS: &[(&str, &str)] = &[
+    ("+", "add"),
+    ("-", "subtract"),
+    ("*", "multiply"),
+    ("/", "divide"),
+    ("quot", "quot"),
+    ("rem", "rem"),
+    ("mod", "modulo"),
+    ("pow", "pow"),
+    ("==", "equal"),
+    ("!=", "not_equal"),
+    ("<", "less_than"),
+    ("<=", "less_than_or_equal"),
+    (">", "greater_than"),
+    (">=", "greater_than_or_equal"),
+    ("abs", "abs"),
+    ("min", "min"),
+    ("max", "max"),
+    ("sqrt", "sqrt"),
+    ("not", "not"),
+    ("nil?", "is_nil"),
+    ("=", "same"),
+    (CASE_EQUAL, "same"),
+    ("list", "list"),
+    ("cons", "cons"),
+    ("first", "first"),
+    ("car", "first"),
+    ("rest", "rest"),
+    ("cdr", "rest"),
+    ("nth", "nth"),
+    ("length", "length"),
+    ("empty?", "is_empty"),
+    ("append", "append"),
+    ("reverse", "reverse"),
+    ("map", "map"),
+    ("filter", "filter"),
+    ("reduce", "reduce"),
+    ("fold", "reduce"),
+    ("range", "range"),
+    ("str", "str"),
+    ("upper", "upper"),
+    ("lower", "lower"),
+    ("trim", "trim"),
+    ("split", "split"),
+    ("join", "join"),
+];
+
+fn builtin(name: &str) -> Option<&'static str> {
+    BUILTINS
+        .iter()
+        .find(|(flop, _)| *flop == name)
+        .map(|(_, rust)| *rust)
+}
+
+/// Builds indented source text.
+#[derive(Default)]
+struct Writer {
+    code: String,
+    indent: usize,
+    at_line_start: bool,
+}
+
+impl Writer {
+    fn write(&mut self, text: &str) {
+        if self.at_line_start {
+            self.code.push_str(&"    ".repeat(self.indent));
+            self.at_line_start = false;
+        }
+
+        self.code.push_str(text);
+    }
+
+    fn newline(&mut self) {
+        self.code.push('\n');
+        self.at_line_start = true;
+    }
+
+    fn line(&mut self, text: &str) {
+        self.write(text);
+        self.newline();
+    }
+
+    fn open(&mut self) {
+        self.write("{");
+        self.indent += 1;
+        self.newline();
+    }
+
+    fn close(&mut self) {
+        self.indent -= 1;
+
+        if !self.at_line_start {
+            self.newline();
+        }
+
+        self.write("}");
+    }
+}
+
+/// How Rust calls a `defn`: `ident` runs it to its value, `step` only up to a
+/// tail call of another function, which it hands back to run next.
+#[derive(Debug, Clone, PartialEq)]
+struct Signature {
+    name: String,
+    ident: String,
+    step: String,
+    required: usize,
+    rest: bool,
+}
+
+impl Signature {
+    fn expected(&self) -> String {
+        if self.rest {
+            format!("at least {}", self.required)
+        } else {
+            self.required.to_string()
+        }
+    }
+}
+
+/// A `defn` and the top-level form it is defined by.
+struct Definition {
+    form: usize,
+    signature: Signature,
+}
+
+/// Where a `recur` jumps back to.
+struct LoopTarget {
+    label: String,
+    bindings: Vec<String>,
+}
+
+/// What a form in tail position may jump to instead of returning a value.
+#[derive(Debug, Clone, Copy, Default)]
+struct Tail {
+    /// In tail position of a `defn`. Calls of the function itself become a jump
+    /// back to its start, and calls of other `defn`s are handed to the trampoline.
+    function: bool,
+    /// Index of the loop a `recur` continues.
+    recur: Option<usize>,
+}
+
+/// The local bindings of the function, or top-level code, being generated.
+struct Scope {
+    names: Names,
+    frames: Vec<HashMap<String, String>>,
+    /// The function whose tail calls to itself run as a loop, and its parameters.
+    function: Option<Signature>,
+    parameters: Vec<String>,
+    /// Assigning an unbound name defines a global at the top level and a local in
+    /// functions.
+    top_level: bool,
+    loops: Vec<LoopTarget>,
+}
+
+impl Scope {
+    fn new(names: Names, top_level: bool) -> Self {
+        Self {
+            names,
+            frames: vec![HashMap::new()],
+            function: None,
+            parameters: Vec::new(),
+            top_level,
+            loops: Vec::new(),
+        }
+    }
+
+    fn local(&self, name: &str) -> Option<&String> {
+        self.frames.iter().rev().find_map(|frame| frame.get(name))
+    }
+
+    /// The identifier of a local `name`, without binding it yet.
+    fn ident(&mut self, name: &str) -> String {
+        self.names.claim(mangle(name), &format!("local {}", name))
+    }
+
+    /// Binds `name` in the innermost frame, returning its identifier.
+    fn bind(&mut self, name: &str) -> String {
+        let ident = self.ident(name);
+
+        self.frames
+            .last_mut()
+            .expect("scope without frames")
+            .insert(name.to_string(), ident.clone());
+
+        ident
+    }
+
+    /// Every visible binding, innermost first.
+    fn visible(&self) -> HashMap<String, String> {
+        let mut visible = HashMap::new();
+
+        for frame in &self.frames {
+            visible.extend(frame.clone());
+        }
+
+        visible
+    }
+}
+
+/// The `main.rs` of a transpiled program, with the Flop origin of its code.
+#[derive(Debug, Clone)]
+pub struct Generated {
+    pub code: String,
+    pub source_map: SourceMap,
+}
+
+/// Transpiles the forms of a Flop file into the `main.rs` of a Rust program.
+/// `defn`s become Rust functions, top-level `setq`s globals, and the remaining
+/// top-level forms run in order in `main`, printing their values like the
+/// interpreter does.
+pub fn transpile(nodes: &Stack<Node>, path: &Path) -> Result<Generated, CompileError> {
+    let mut codegen = Codegen::default();
+
+    codegen.declare(nodes)?;
+
+    codegen
+        .out
+        .line(&format!("//! Generated by flop from {}.", path.display()));
+    codegen.out.newline();
+    codegen
+        .out
+        .line("#![allow(non_snake_case, non_upper_case_globals, dead_code)]");
+    codegen
+        .out
+        .line("#![allow(unused_mut, unused_variables, unreachable_code)]");
+    codegen.out.newline();
+    codegen.out.line("use flop_runtime as flop;");
+    codegen.out.line("use flop::{Error, Value};");
+
+    codegen.globals_block();
+
+    for (index, node) in nodes.data.iter().enumerate() {
+        if let Node::FunctionDefinition(fd) = node {
+            codegen.form = index;
+            codegen.out.newline();
+            codegen.function(fd)?;
+        }
+    }
+
+    codegen.out.newline();
+    codegen.out.line("fn main() {");
+    codegen.out.line("    flop::run(program);");
+    codegen.out.line("}");
+    codegen.out.newline();
+    codegen.out.write("fn program() -> Result<(), Error> ");
+    codegen.out.open();
+
+    let mut scope = Scope::new(codegen.names.clone(), true);
+
+    for (index, node) in nodes.data.iter().enumerate() {
+        codegen.form = index;
+
+        match node {
+            Node::FunctionDefinition(_) | Node::Documentation(_) => {}
+            Node::VariableDefinition(vd) => codegen.assign(vd, &mut scope)?,
+            node => {
+                codegen.out.write("println!(\"{}\", ");
+                codegen.expr(node, &mut scope, Tail::default())?;
+                codegen.out.line(");");
+            }
+        }
+    }
+
+    codegen.out.line("Ok(())");
+    codegen.out.close();
+    codegen.out.newline();
+
+    Ok(Generated {
+        code: codegen.out.code,
+        source_map: codegen.source_map,
+    })
+}
+
+#[derive(Default)]
+struct Codegen {
+    out: Writer,
+    names: Names,
+    functions: HashMap<String, Vec<Definition>>,
+    /// Flop names of globals and the statics holding them.
+    globals: BTreeMap<String, String>,
+    /// Index of the top-level form being generated, calls resolve to the `defn`
+    /// in effect there.
+    form: usize,
+    labels: usize,
+    source_map: SourceMap,
+}
+
+impl Codegen {
+    /// Names every `defn` and top-level global before any code refers to them.
+    fn declare(&mut self, nodes: &Stack<Node>) -> Result<(), CompileError> {
+        for (index, node) in nodes.data.iter().enumerate() {
+            match node {
+                Node::FunctionDefinition(fd) => {
+                    let (required, rest) = single_arity(fd)?;
+
+                    let name = fd.name.token.clone();
+                    let ident = self
+                        .names
+                        .claim(mangle(&name), &format!("fn {} {}", name, index));
+                    let step = self.names.claim(
+                        format!("{}_step", ident),
+                        &format!("step {} {}", name, index),
+                    );
+
+                    self.functions
+                        .entry(name.clone())
+                        .or_default()
+                        .push(Definition {
+                            form: index,
+                            signature: Signature {
+                                name,
+                                ident,
+                                step,
+                                required: required.len(),
+                                rest: rest.is_some(),
+                            },
+                        });
+                }
+                node => {
+                    let mut assigned = Vec::new();
+                    assignments(node, &mut assigned);
+
+                    for name in assigned {
+                        let ident = self
+                            .names
+                            .claim(mangle(&name).to_uppercase(), &format!("global {}", name));
+
+                        self.globals.insert(name, ident);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn globals_block(&mut self) {
+        if self.globals.is_empty() {
+            return;
+        }
+
+        self.out.newline();
+        self.out.write("thread_local! ");
+        self.out.open();
+
+        for (name, ident) in &self.globals {
+            self.out.line(&format!(
+                "static {}: flop::Global = const {{ flop::Global::new({:?}) }};",
+                ident, name
+            ));
+        }
+
+        self.out.close();
+        self.out.newline();
+    }
+
+    /// The `defn` a call at the current form refers to: the last definition before
+    /// it, or the first one when the name is only defined further down.
+    fn resolve(&self, name: &str) -> Option<&Signature> {
+        let definitions = self.functions.get(name)?;
+
+        definitions
+            .iter()
+            .rev()
+            .find(|definition| definition.form <= self.form)
+            .or_else(|| definitions.first())
+            .map(|definition| &definition.signature)
+    }
+
+    fn function(&mut self, fd: &FunctionDefinition) -> Result<(), CompileError> {
+        let signature = self
+            .resolve(&fd.name.token)
+            .cloned()
+            .expect("defn declared before generating it");
+
+        let (required, rest) = single_arity(fd)?;
+        let body = &fd.arities.data[0].body;
+        let start = self.out.code.len();
+
+        for line in fd.docstrings.token.lines() {
+            self.out.line(format!("/// {}", line.trim()).trim_end());
+        }
+
+        let mut scope = Scope::new(self.names.clone(), false);
+
+        scope.parameters = required
+            .iter()
+            .chain(rest.iter())
+            .enumerate()
+            .map(|(index, param)| match param {
+                Some(name) => scope.bind(name),
+                None => format!("__ignored{}", index),
+            })
+            .collect();
+
+        let params = scope
+            .parameters
+            .iter()
+            .map(|ident| format!("mut {}: Value", ident))
+            .collect::<Vec<_>>();
+
+        self.out.write(&format!(
+            "fn {}({}) -> Result<Value, Error> ",
+            signature.ident,
+            params.join(", ")
+        ));
+        self.out.open();
+        self.out.line("flop::enter()?;");
+        self.out.line(&format!(
+            "flop::trampoline({}({}))",
+            signature.step,
+            scope.parameters.join(", ")
+        ));
+        self.out.close();
+        self.out.newline();
+        self.out.newline();
+
+        self.out.write(&format!(
+            "fn {}({}) -> Result<flop::Step, Error> ",
+            signature.step,
+            params.join(", ")
+        ));
+        self.out.open();
+
+        let loops = self_tail_call(body, &fd.name.token);
+
+        if loops {
+            scope.function = Some(signature);
+            self.out.write("'tail: loop ");
+            self.out.open();
+        }
+
+        self.declare_locals(body, &mut scope);
+
+        let tail = Tail {
+            function: true,
+            recur: None,
+        };
+
+        self.body(
+            body,
+            &mut scope,
+            tail,
+            if loops {
+                "return Ok(flop::Step::Value("
+            } else {
+                "Ok(flop::Step::Value("
+            },
+        )?;
+        self.out.write(")");
+
+        if loops {
+            self.out.line(";");
+            self.out.close();
+            self.out.newline();
+        }
+
+        self.out.close();
+        self.out.newline();
+
+        self.source_map.record(start, self.out.code.len(), &fd.name);
+
+        Ok(())
+    }
+
+    /// Declares the names a function body assigns without binding them otherwise,
+    /// `setq` defines locals inside functions.
+    fn declare_locals(&mut self, body: &Stack<Node>, scope: &mut Scope) {
+        let mut assigned = Vec::new();
+
+        for node in &body.data {
+            assignments(node, &mut assigned);
+        }
+
+        for name in assigned {
+            if scope.local(&name).is_none() {
+                let ident = scope.bind(&name);
+                self.out.line(&format!("let mut {} = Value::Nil;", ident));
+            }
+        }
+    }
+
+    /// Every form but the last as a statement, then the last wrapped in `open` and
+    /// a closing parenthesis.
+    fn body(
+        &mut self,
+        body: &Stack<Node>,
+        scope: &mut Scope,
+        tail: Tail,
+        open: &str,
+    ) -> Result<(), CompileError> {
+        for node in body.data.iter().take(body.data.len().saturating_sub(1)) {
+            self.stmt(node, scope)?;
+        }
+
+        self.out.write(open);
+
+        match body.data.back() {
+            Some(node) => self.expr(node, scope, tail)?,
+            None => self.out.write("Value::Nil"),
+        }
+
+        self.out.write(")");
+
+        Ok(())
+    }
+
+    /// A block evaluating `body` in a scope of its own.
+    fn block(
+        &mut self,
+        body: &Stack<Node>,
+        scope: &mut Scope,
+        tail: Tail,
+    ) -> Result<(), CompileError> {
+        self.out.open();
+        scope.frames.push(HashMap::new());
+
+        for node in body.data.iter().take(body.data.len().saturating_sub(1)) {
+            self.stmt(node, scope)?;
+        }
+
+        match body.data.back() {
+            Some(node) => self.expr(node, scope, tail)?,
+            None => self.out.write("Value::Nil"),
+        }
+
+        scope.frames.pop();
+        self.out.close();
+
+        Ok(())
+    }
+
+    /// Runs every form of `body` for its effects.
+    fn stmts(&mut self, body: &Stack<Node>, scope: &mut Scope) -> Result<(), CompileError> {
+        for node in &body.data {
+            self.stmt(node, scope)?;
+        }
+
+        Ok(())
+    }
+
+    fn stmt(&mut self, node: &Node, scope: &mut Scope) -> Result<(), CompileError> {
+        match node {
+            Node::VariableDefinition(vd) => self.assign(vd, scope),
+            node => {
+                self.expr(node, scope, Tail::default())?;
+                self.out.line(";");
+                Ok(())
+            }
+        }
+    }
+
+    fn assign(&mut self, vd: &VariableDefinition, scope: &mut Scope) -> Result<(), CompileError> {
+        let start = self.out.code.len();
+        let name = &vd.name.token;
+
+        if let Some(ident) = scope.local(name).cloned() {
+            self.out.write(&format!("{} = ", ident));
+            self.expr(&vd.assignment, scope, Tail::default())?;
+            self.out.line(";");
+        } else if let Some(ident) = self.globals.get(name).filter(|_| scope.top_level).cloned() {
+            self.out.write(&format!("flop::set(&{}, ", ident));
+            self.expr(&vd.assignment, scope, Tail::default())?;
+            self.out.line(");");
+        } else {
+            let ident = scope.bind(name);
+            self.out.write(&format!("let mut {} = ", ident));
+            self.expr(&vd.assignment, scope, Tail::default())?;
+            self.out.line(";");
+        }
+
+        self.source_map.record(start, self.out.code.len(), &vd.name);
+
+        Ok(())
+    }
+
+    /// A Rust expression evaluating to the `Value` of `node`.
+    fn expr(&mut self, node: &Node, scope: &mut Scope, tail: Tail) -> Result<(), CompileError> {
+        let start = self.out.code.len();
+
+        self.node(node, scope, tail)?;
+
+        if let Some(token) = token_of(node) {
+            self.source_map.record(start, self.out.code.len(), token);
+        }
+
+        Ok(())
+    }
+
+    fn node(&mut self, node: &Node, scope: &mut Scope, tail: Tail) -> Result<(), CompileError> {
+        match node {
+            Node::Literal(token) => {
+                let literal = literal(token)?;
+                self.out.write(&literal);
+            }
+            Node::VariableCall(vc) => self.variable(&vc.name, scope)?,
+            Node::VariableDefinition(vd) => {
+                self.out.open();
+                self.assign(vd, scope)?;
+                self.out.write("Value::Nil");
+                self.out.close();
+            }
+            Node::FunctionCall(call) => self.call(call, scope, tail)?,
+            Node::Conditional(conditional) => self.conditional(conditional, scope, tail)?,
+            Node::Logical(logical) => self.logical(logical, scope, tail)?,
+            Node::Match(mt) => self.match_form(mt, scope, tail)?,
+            Node::List(list) => {
+                self.out.write("Value::list(vec![");
+                self.list(&list.data, scope)?;
+                self.out.write("])");
+            }
+            Node::Lambda(lambda) => self.lambda(lambda, scope)?,
+            Node::While(wl) => self.while_loop(wl, scope)?,
+            Node::Loop(lp) => self.loop_form(lp, scope, tail)?,
+            Node::Recur(recur) => self.recur(recur, scope, tail)?,
+            Node::DoTimes(iteration) => self.dotimes(iteration, scope)?,
+            Node::DoSeq(iteration) => self.doseq(iteration, scope)?,
+            node => {
+                let (token, what) = unsupported(node);
+
+                return Err(CompileError::Unsupported {
+                    token: token.cloned().unwrap_or_else(|| {
+                        Token::new("", TokenKind::Error, 0, 0, 0, Path::new(""))
+                    }),
+                    what,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `node` as the receiver of a method call, compound expressions are wrapped
+    /// in parentheses.
+    fn operand(&mut self, node: &Node, scope: &mut Scope) -> Result<(), CompileError> {
+        let simple = matches!(
+            node,
+            Node::Literal(_) | Node::VariableCall(_) | Node::FunctionCall(_) | Node::List(_)
+        );
+
+        if !simple {
+            self.out.write("(");
+        }
+
+        self.expr(node, scope, Tail::default())?;
+
+        if !simple {
+            self.out.write(")");
+        }
+
+        Ok(())
+    }
+
+    fn list(&mut self, items: &Stack<Node>, scope: &mut Scope) -> Result<(), CompileError> {
+        for (index, item) in items.data.iter().enumerate() {
+            if index > 0 {
+                self.out.write(", ");
+            }
+
+            self.expr(item, scope, Tail::default())?;
+        }
+
+        Ok(())
+    }
+
+    fn variable(&mut self, name: &Token, scope: &mut Scope) -> Result<(), CompileError> {
+        if let Some(ident) = scope.local(&name.token) {
+            let code = format!("{}.clone()", ident);
+            self.out.write(&code);
+        } else if let Some(ident) = self.globals.get(&name.token) {
+            let code = format!("flop::get(&{})?", ident);
+            self.out.write(&code);
+        } else if let Some(signature) = self.resolve(&name.token).cloned() {
+            self.function_value(&signature);
+        } else if let Some(rust) = builtin(&name.token) {
+            let code = format!("Value::builtin({:?}, flop::{})", name.token, rust);
+            self.out.write(&code);
+        } else {
+            return Err(CompileError::UnknownVariable(name.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// A `defn` as a value, checking the arguments it is called with.
+    fn function_value(&mut self, signature: &Signature) {
+        self.out
+            .write(&format!("Value::function({:?}, |args| ", signature.name));
+        self.out.open();
+
+        let check = if signature.rest {
+            "expect_at_least"
+        } else {
+            "expect_arity"
+        };
+
+        self.out.line(&format!(
+            "flop::{}({:?}, &args, {})?;",
+            check, signature.name, signature.required
+        ));
+
+        let mut args = (0..signature.required)
+            .map(|index| format!("args[{}].clone()", index))
+            .collect::<Vec<_>>();
+
+        if signature.rest {
+            args.push(format!(
+                "Value::list(args[{}..].to_vec())",
+                signature.required
+            ));
+        }
+
+        self.out
+            .write(&format!("{}({})", signature.ident, args.join(", ")));
+        self.out.close();
+        self.out.write(")");
+    }
+
+    fn call(
+        &mut self,
+        call: &FunctionCall,
+        scope: &mut Scope,
+        tail: Tail,
+    ) -> Result<(), CompileError> {
+        let name = &call.name.token;
+
+        if let Some(ident) = scope.local(name).cloned() {
+            self.out.write(&format!("{}.call(vec![", ident));
+            self.list(&call.arguments, scope)?;
+            self.out.write("])?");
+            return Ok(());
+        }
+
+        if let Some(signature) = self.resolve(name).cloned() {
+            let found = call.arguments.data.len();
+
+            if found < signature.required || (!signature.rest && found > signature.required) {
+                return Err(CompileError::ArityError {
+                    token: call.name.clone(),
+                    expected: signature.expected(),
+                    found,
+                });
+            }
+
+            if tail.function && scope.function.as_ref() == Some(&signature) {
+                return self.jump(&call.arguments, &signature, scope);
+            }
+
+            if tail.function {
+                return self.hand_over(&call.arguments, &signature, scope);
+            }
+
+            self.out.write(&format!("{}(", signature.ident));
+            self.arguments(&call.arguments, &signature, scope)?;
+            self.out.write(")?");
+            return Ok(());
+        }
+
+        if let Some(ident) = self.globals.get(name).cloned() {
+            self.out
+                .write(&format!("flop::get(&{})?.call(vec![", ident));
+            self.list(&call.arguments, scope)?;
+            self.out.write("])?");
+            return Ok(());
+        }
+
+        if let Some(rust) = builtin(name) {
+            self.out.write(&format!("flop::{}(vec![", rust));
+            self.list(&call.arguments, scope)?;
+            self.out.write("])?");
+            return Ok(());
+        }
+
+        Err(CompileError::UnknownFunction(call.name.clone()))
+    }
+
+    /// The arguments of a call to `signature`, extra ones collected in a list.
+    fn arguments(
+        &mut self,
+        arguments: &Stack<Node>,
+        signature: &Signature,
+        scope: &mut Scope,
+    ) -> Result<(), CompileError> {
+        for (index, argument) in arguments.data.iter().enumerate() {
+            if index > 0 {
+                self.out.write(", ");
+            }
+
+            if signature.rest && index == signature.required {
+                self.out.write("Value::list(vec![");
+            }
+
+            self.expr(argument, scope, Tail::default())?;
+        }
+
+        if signature.rest {
+            if arguments.data.len() == signature.required {
+                if signature.required > 0 {
+                    self.out.write(", ");
+                }
+
+                self.out.write("Value::list(vec![");
+            }
+
+            self.out.write("])");
+        }
+
+        Ok(())
+    }
+
+    /// A tail call of the function being defined, rebinding its parameters and
+    /// starting over.
+    fn jump(
+        &mut self,
+        arguments: &Stack<Node>,
+        signature: &Signature,
+        scope: &mut Scope,
+    ) -> Result<(), CompileError> {
+        self.out.open();
+        self.out.write("let __next = (");
+        self.arguments(arguments, signature, scope)?;
+        self.out.line(",);");
+
+        for (index, ident) in scope.parameters.iter().enumerate() {
+            self.out.line(&format!("{} = __next.{};", ident, index));
+        }
+
+        self.out.write("continue 'tail;");
+        self.out.close();
+
+        Ok(())
+    }
+
+    fn conditional(
+        &mut self,
+        conditional: &Conditional,
+        scope: &mut Scope,
+        tail: Tail,
+    ) -> Result<(), CompileError> {
+        self.out.write("if ");
+        self.operand(&conditional.condition, scope)?;
+        self.out.write(".is_truthy() ");
+        self.block(&conditional.true_expression, scope, tail)?;
+        self.out.write(" else ");
+        self.block(&conditional.false_expression, scope, tail)
+    }
+
+    /// `and` and `or` return the first operand deciding the result.
+    fn logical(
+        &mut self,
+        logical: &Logical,
+        scope: &mut Scope,
+        tail: Tail,
+    ) -> Result<(), CompileError> {
+        let is_and = logical.operator.token == "and";
+
+        self.logical_operands(
+            &logical.operands.data.iter().collect::<Vec<_>>(),
+            is_and,
+            scope,
+            tail,
+        )
+    }
+
+    fn logical_operands(
+        &mut self,
+        operands: &[&Node],
+        is_and: bool,
+        scope: &mut Scope,
+        tail: Tail,
+    ) -> Result<(), CompileError> {
+        match operands {
+            [] => self.out.write(&format!("Value::Bool({})", is_and)),
+            [last] => self.expr(last, scope, tail)?,
+            [first, rest @ ..] => {
+                self.out.open();
+                self.out.write("let __value = ");
+                self.expr(first, scope, Tail::default())?;
+                self.out.line(";");
+                self.out.write(if is_and {
+                    "if __value.is_truthy() "
+                } else {
+                    "if !__value.is_truthy() "
+                });
+                self.out.open();
+                self.logical_operands(rest, is_and, scope, tail)?;
+                self.out.close();
+                self.out.write(" else ");
+                self.out.open();
+                self.out.write("__value");
+                self.out.close();
+                self.out.close();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A `match` whose first clause takes any value, as `case` is lowered to, binds
+    /// the value and runs that clause. Other matches are not translated.
+    fn match_form(
+        &mut self,
+        mt: &Match,
+        scope: &mut Scope,
+        tail: Tail,
+    ) -> Result<(), CompileError> {
+        let Some(clause) = mt
+            .clauses
+            .data
+            .front()
+            .filter(|clause| clause.guard.is_none())
+        else {
+            return Err(CompileError::Unsupported {
+                token: mt.token.clone(),
+                what: "match",
+            });
+        };
+
+        self.out.open();
+        scope.frames.push(HashMap::new());
+
+        match &clause.pattern {
+            Pattern::Bind(name) => {
+                // the value is evaluated before the name is bound
+                let ident = scope.ident(&name.token);
+                self.out.write(&format!("let mut {} = ", ident));
+                self.expr(&mt.value, scope, Tail::default())?;
+                self.out.line(";");
+
+                scope.bind(&name.token);
+            }
+            Pattern::Wildcard(_) => {
+                self.out.write("let _ = ");
+                self.expr(&mt.value, scope, Tail::default())?;
+                self.out.line(";");
+            }
+            _ => {
+                return Err(CompileError::Unsupported {
+                    token: mt.token.clone(),
+                    what: "match",
+                })
+            }
+        }
+
+        self.block(&clause.body, scope, tail)?;
+
+        scope.frames.pop();
+        self.out.close();
+
+        Ok(())
+    }
+
+    /// A closure capturing copies of the locals its body refers to.
+    fn lambda(&mut self, lambda: &Lambda, scope: &mut Scope) -> Result<(), CompileError> {
+        if lambda.arities.data.len() != 1 {
+            return Err(CompileError::Unsupported {
+                token: lambda.token.clone(),
+                what: "a lambda with several arities",
+            });
+        }
+
+        let arity = &lambda.arities.data[0];
+        let (required, rest) = parameters(&arity.parameters, &lambda.token)?;
+
+        let mut referenced = HashSet::new();
+        for node in &arity.body.data {
+            symbols(node, &mut referenced);
+        }
+
+        let captured = scope
+            .visible()
+            .into_iter()
+            .filter(|(name, _)| referenced.contains(name))
+            .collect::<BTreeMap<_, _>>();
+
+        self.out.open();
+
+        for ident in captured.values() {
+            self.out
+                .line(&format!("let {} = {}.clone();", ident, ident));
+        }
+
+        self.out.write("Value::function(\"fn\", move |args| ");
+        self.out.open();
+
+        let check = if rest.is_some() {
+            "expect_at_least"
+        } else {
+            "expect_arity"
+        };
+
+        self.out.line(&format!(
+            "flop::{}(\"fn\", &args, {})?;",
+            check,
+            required.len()
+        ));
+
+        let mut inner = Scope::new(scope.names.clone(), false);
+
+        for (name, ident) in &captured {
+            inner.frames[0].insert(name.clone(), ident.clone());
+            self.out
+                .line(&format!("let mut {} = {}.clone();", ident, ident));
+        }
+
+        for (index, param) in required.iter().enumerate() {
+            if let Some(name) = param {
+                let ident = inner.bind(name);
+                self.out
+                    .line(&format!("let mut {} = args[{}].clone();", ident, index));
+            }
+        }
+
+        if let Some(Some(name)) = &rest {
+            let ident = inner.bind(name);
+            self.out.line(&format!(
+                "let mut {} = Value::list(args[{}..].to_vec());",
+                ident,
+                required.len()
+            ));
+        }
+
+        self.declare_locals(&arity.body, &mut inner);
+        self.body(&arity.body, &mut inner, Tail::default(), "Ok(")?;
+
+        self.out.close();
+        self.out.write(")");
+        self.out.close();
+
+        Ok(())
+    }
+
+    fn while_loop(&mut self, wl: &While, scope: &mut Scope) -> Result<(), CompileError> {
+        self.out.open();
+        self.out.write("while ");
+        self.operand(&wl.condition, scope)?;
+        self.out.write(".is_truthy() ");
+        self.out.open();
+        scope.frames.push(HashMap::new());
+        self.stmts(&wl.body, scope)?;
+        scope.frames.pop();
+        self.out.close();
+        self.out.newline();
+        self.out.write("Value::Nil");
+        self.out.close();
+
+        Ok(())
+    }
+
+    /// `(loop [name value ...] body...)` as a labelled loop whose `recur`s rebind
+    /// the names and continue.
+    fn loop_form(&mut self, lp: &Loop, scope: &mut Scope, tail: Tail) -> Result<(), CompileError> {
+        self.out.open();
+        scope.frames.push(HashMap::new());
+
+        let mut bindings = Vec::new();
+
+        for (name, value) in lp.names.data.iter().zip(&lp.values.data) {
+            // the value is evaluated before the name is bound
+            let ident = scope.ident(&name.token);
+            self.out.write(&format!("let mut {} = ", ident));
+            self.expr(value, scope, Tail::default())?;
+            self.out.line(";");
+
+            scope.bind(&name.token);
+            bindings.push(ident);
+        }
+
+        self.labels += 1;
+        let label = format!("'loop{}", self.labels);
+
+        scope.loops.push(LoopTarget {
+            label: label.clone(),
+            bindings,
+        });
+
+        let inner = Tail {
+            function: tail.function,
+            recur: Some(scope.loops.len() - 1),
+        };
+
+        self.out.write(&format!("{}: loop ", label));
+        self.out.open();
+        self.out.write(&format!("break {} (", label));
+        self.block(&lp.body, scope, inner)?;
+        self.out.line(");");
+        self.out.close();
+
+        scope.loops.pop();
+        scope.frames.pop();
+        self.out.close();
+
+        Ok(())
+    }
+
+    /// A tail call of another `defn`, returned to the trampoline of the function
+    /// that was called so the stack doesn't grow.
+    fn hand_over(
+        &mut self,
+        arguments: &Stack<Node>,
+        signature: &Signature,
+        scope: &mut Scope,
+    ) -> Result<(), CompileError> {
+        let count = signature.required + usize::from(signature.rest);
+
+        self.out.open();
+
+        if count > 0 {
+            self.out.write("let __next = (");
+            self.arguments(arguments, signature, scope)?;
+            self.out.line(",);");
+        }
+
+        let next = (0..count)
+            .map(|index| format!("__next.{}", index))
+            .collect::<Vec<_>>();
+
+        self.out.write(&format!(
+            "return Ok(flop::Step::Call(Box::new(move || {}({}))));",
+            signature.step,
+            next.join(", ")
+        ));
+        self.out.close();
+
+        Ok(())
+    }
+
+    fn recur(&mut self, recur: &Recur, scope: &mut Scope, tail: Tail) -> Result<(), CompileError> {
+        let Some(index) = tail.recur else {
+            return Err(CompileError::Unsupported {
+                token: recur.token.clone(),
+                what: "recur outside of a loop",
+            });
+        };
+
+        let (label, bindings) = {
+            let target = &scope.loops[index];
+            (target.label.clone(), target.bindings.clone())
+        };
+
+        self.out.open();
+        self.out.write("let __next = (");
+        self.list(&recur.arguments, scope)?;
+        self.out.line(",);");
+
+        for (position, ident) in bindings.iter().enumerate() {
+            self.out.line(&format!("{} = __next.{};", ident, position));
+        }
+
+        self.out.write(&format!("continue {};", label));
+        self.out.close();
+
+        Ok(())
+    }
+
+    fn dotimes(&mut self, iteration: &Iteration, scope: &mut Scope) -> Result<(), CompileError> {
+        self.out.open();
+        self.out.write("let __count = ");
+        self.operand(&iteration.source, scope)?;
+        self.out.line(".as_integer()?;");
+        self.out.write("for __index in 0..__count ");
+        self.out.open();
+
+        scope.frames.push(HashMap::new());
+        let ident = scope.bind(&iteration.name.token);
+        self.out
+            .line(&format!("let mut {} = Value::Integer(__index);", ident));
+        self.stmts(&iteration.body, scope)?;
+        scope.frames.pop();
+
+        self.out.close();
+        self.out.newline();
+        self.out.write("Value::Nil");
+        self.out.close();
+
+        Ok(())
+    }
+
+    fn doseq(&mut self, iteration: &Iteration, scope: &mut Scope) -> Result<(), CompileError> {
+        self.out.open();
+        self.out.write("let __items = ");
+        self.operand(&iteration.source, scope)?;
+        self.out.line(".as_list()?;");
+        self.out.write("for __item in __items.iter() ");
+        self.out.open();
+
+        scope.frames.push(HashMap::new());
+        let ident = scope.bind(&iteration.name.token);
+        self.out
+            .line(&format!("let mut {} = __item.clone();", ident));
+        self.stmts(&iteration.body, scope)?;
+        scope.frames.pop();
+
+        self.out.close();
+        self.out.newline();
+        self.out.write("Value::Nil");
+        self.out.close();
+
+        Ok(())
+    }
+}
+
+/// Required parameters and the rest parameter, `None` stands for `_`.
+type ParameterNames = (Vec<Option<String>>, Option<Option<String>>);
+
+/// The parameters of a `defn`, which must have exactly one arity.
+fn single_arity(fd: &FunctionDefinition) -> Result<ParameterNames, CompileError> {
+    match fd.arities.data.len() {
+        1 => parameters(&fd.arities.data[0].parameters, &fd.name),
+        _ => Err(CompileError::Unsupported {
+            token: fd.name.clone(),
+            what: "a function with several arities",
+        }),
+    }
+}
+
+fn parameters(parameters: &Parameters, token: &Token) -> Result<ParameterNames, CompileError> {
+    if !parameters.optional.is_empty() || !parameters.keywords.is_empty() {
+        return Err(CompileError::Unsupported {
+            token: token.clone(),
+            what: "an optional or keyword parameter",
+        });
+    }
+
+    let name = |pattern: &Pattern| match pattern {
+        Pattern::Bind(name) => Ok(Some(name.token.clone())),
+        Pattern::Wildcard(_) => Ok(None),
+        _ => Err(CompileError::Unsupported {
+            token: token.clone(),
+            what: "a destructuring parameter",
+        }),
+    };
+
+    let required = parameters
+        .required
+        .data
+        .iter()
+        .map(name)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let rest = parameters.rest.as_ref().map(name).transpose()?;
+
+    Ok((required, rest))
+}
+
+/// A Rust expression for a literal token.
+fn literal(token: &Token) -> Result<String, CompileError> {
+    let code = match token.token_kind {
+        TokenKind::Integer => format!("Value::Integer({})", token.token),
+        TokenKind::Float => match token.token.parse::<f64>() {
+            Ok(number) => format!("Value::Float({:?})", number),
+            Err(_) => format!("Value::Float({})", token.token),
+        },
+        TokenKind::Bool => format!("Value::Bool({})", token.token == "true"),
+        TokenKind::Nil => "Value::Nil".to_string(),
+        TokenKind::Keyword => format!("Value::keyword({:?})", &token.token[1..]),
+        TokenKind::StringLiteral | TokenKind::DocString => {
+            format!("Value::from({:?})", token.token)
+        }
+        _ => {
+            return Err(CompileError::Unsupported {
+                token: token.clone(),
+                what: "this literal",
+            })
+        }
+    };
+
+    Ok(code)
+}
+
+/// Names `setq` assigns in `node`, leaving out function and lambda bodies.
+fn assignments(node: &Node, assigned: &mut Vec<String>) {
+    if let Node::VariableDefinition(vd) = node {
+        if !assigned.contains(&vd.name.token) {
+            assigned.push(vd.name.token.clone());
+        }
+    }
+
+    if matches!(node, Node::FunctionDefinition(_) | Node::Lambda(_)) {
+        return;
+    }
+
+    for child in children(node) {
+        assignments(child, assigned);
+    }
+}
+
+/// Every symbol `node` refers to, used to find what a lambda captures.
+fn symbols(node: &Node, found: &mut HashSet<String>) {
+    match node {
+        Node::VariableCall(vc) => {
+            found.insert(vc.name.token.clone());
+        }
+        Node::FunctionCall(call) => {
+            found.insert(call.name.token.clone());
+        }
+        Node::VariableDefinition(vd) => {
+            found.insert(vd.name.token.clone());
+        }
+        _ => {}
+    }
+
+    for child in children(node) {
+        symbols(child, found);
+    }
+}
+
+/// Whether `body` calls the function `name` in tail position.
+fn self_tail_call(body: &Stack<Node>, name: &str) -> bool {
+    body.data.back().is_some_and(|last| tail_calls(last, name))
+}
+
+fn tail_calls(node: &Node, name: &str) -> bool {
+    match node {
+        Node::FunctionCall(call) => call.name.token == name,
+        Node::Conditional(conditional) => {
+            self_tail_call(&conditional.true_expression, name)
+                || self_tail_call(&conditional.false_expression, name)
+        }
+        Node::Match(mt) => mt
+            .clauses
+            .data
+            .iter()
+            .any(|clause| self_tail_call(&clause.body, name)),
+        Node::Logical(logical) => logical
+            .operands
+            .data
+            .back()
+            .is_some_and(|last| tail_calls(last, name)),
+        Node::Loop(lp) => self_tail_call(&lp.body, name),
+        _ => false,
+    }
+}
+
+/// The forms directly nested in `node` that the compiler looks into.
+fn children(node: &Node) -> Vec<&Node> {
+    let mut children = Vec::new();
+
+    match node {
+        Node::VariableDefinition(vd) => children.push(&*vd.assignment),
+        Node::FunctionCall(call) => children.extend(&call.arguments.data),
+        Node::Conditional(conditional) => {
+            children.push(&*conditional.condition);
+            children.extend(&conditional.true_expression.data);
+            children.extend(&conditional.false_expression.data);
+        }
+        Node::Match(mt) => {
+            children.push(&*mt.value);
+
+            for clause in &mt.clauses.data {
+                children.extend(clause.guard.as_deref());
+                children.extend(&clause.body.data);
+            }
+        }
+        Node::Logical(logical) => children.extend(&logical.operands.data),
+        Node::List(list) => children.extend(&list.data.data),
+        Node::Lambda(lambda) => {
+            for arity in &lambda.arities.data {
+                children.extend(&arity.body.data);
+            }
+        }
+        Node::While(wl) => {
+            children.push(&*wl.condition);
+            children.extend(&wl.body.data);
+        }
+        Node::Loop(lp) => {
+            children.extend(&lp.values.data);
+            children.extend(&lp.body.data);
+        }
+        Node::Recur(recur) => children.extend(&recur.arguments.data),
+        Node::DoTimes(iteration) | Node::DoSeq(iteration) => {
+            children.push(&*iteration.source);
+            children.extend(&iteration.body.data);
+        }
+        _ => {}
+    }
+
+    children
+}
+
+/// The token to report a form the compiler cannot translate at, and what it is.
+fn unsupported(node: &Node) -> (Option<&Token>, &'static str) {
+    match node {
+        Node::FunctionDefinition(fd) => (Some(&fd.name), "a defn inside another form"),
+        Node::StructDefinition(sd) => (Some(&sd.name), "defstruct"),
+        Node::EnumDefinition(ed) => (Some(&ed.name), "defenum"),
+        Node::MultiDefinition(md) => (Some(&md.name), "defmulti"),
+        Node::MethodDefinition(md) => (Some(&md.name), "defmethod"),
+        Node::Namespace(ns) => (Some(&ns.token), "ns"),
+        Node::Require(rq) => (Some(&rq.token), "require"),
+        Node::Try(tr) => (Some(&tr.token), "try"),
+        Node::HandlerBind(hb) => (Some(&hb.token), "handler-bind"),
+        Node::RestartCase(rc) => (Some(&rc.token), "restart-case"),
+        Node::Map(map) => (map.data.data.front().and_then(first_token), "a map"),
+        Node::Set(set) => (set.data.data.front().and_then(first_token), "a set"),
+        Node::Documentation(doc) => (Some(&doc.commentary), "documentation"),
+        node => (first_token(node), "this form"),
+    }
+}
+
+/// The token errors in the code generated for `node` are reported at.
+fn token_of(node: &Node) -> Option<&Token> {
+    match node {
+        Node::Match(mt) => Some(&mt.token),
+        Node::Lambda(lambda) => Some(&lambda.token),
+        Node::Logical(logical) => Some(&logical.operator),
+        Node::While(wl) => Some(&wl.token),
+        Node::Loop(lp) => Some(&lp.token),
+        Node::Recur(recur) => Some(&recur.token),
+        Node::DoTimes(iteration) | Node::DoSeq(iteration) => Some(&iteration.token),
+        Node::VariableDefinition(vd) => Some(&vd.name),
+        node => first_token(node),
+    }
+}
+
+fn first_token(node: &Node) -> Option<&Token> {
+    match node {
+        Node::Literal(token) => Some(token),
+        Node::VariableCall(vc) => Some(&vc.name),
+        Node::FunctionCall(call) => Some(&call.name),
+        node => children(node).into_iter().find_map(first_token),
+    }
+}
@@ -0,0 +1,52 @@
+use flop_frontend::token::Token;
+use miette::Diagnostic;
+use thiserror::Error;
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum CompileError {
+    #[error("{what} is not supported by the compiler yet")]
+    Unsupported {
+        #[source_code]
+        #[label("Only the interpreter can run this")]
+        token: Token,
+        what: &'static str,
+    },
+
+    #[error("unknown function: {}", .0.token)]
+    UnknownFunction(
+        #[source_code]
+        #[label("Neither defined nor built in")]
+        Token,
+    ),
+
+    #[error("unbound variable: {}", .0.token)]
+    UnknownVariable(
+        #[source_code]
+        #[label("Never assigned with setq")]
+        Token,
+    ),
+
+    #[error("arity error: expected {expected} arguments, found {found}")]
+    ArityError {
+        #[source_code]
+        #[label("Wrong number of arguments")]
+        token: Token,
+        expected: String,
+        found: usize,
+    },
+
+    #[error("rustc rejected the generated code: {message}")]
+    Rustc {
+        #[source_code]
+        #[label("Generated from this form")]
+        token: Token,
+        message: String,
+    },
+
+    #[error("cargo failed to build the generated project")]
+    #[diagnostic(help("{output}"))]
+    Cargo { output: String },
+
+    #[error("cannot write the project: {0}")]
+    Io(#[from] std::io::Error),
+}
@@ -1,14 +1,13 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
+//! Compiles Flop programs to Rust. [`codegen`] turns the parsed forms into the
+//! source of a Rust program running on `flop-runtime`, [`project`] writes it out
+//! as a Cargo project and [`native`] builds that into an executable.
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub mod codegen;
+pub mod error;
+pub mod names;
+pub mod native;
+pub mod project;
+pub mod source_map;
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub use codegen::{transpile, Generated};
+pub use error::CompileError;
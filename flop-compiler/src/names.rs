@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Names the generated code refers to itself.
+const RESERVED: &[&str] = &[
+    "flop", "main", "program", "args", "Value", "Error", "Result", "Ok", "Err", "Some", "None",
+    "Vec", "vec", "println",
+];
+
+/// A Rust identifier for a Flop name, `nil?` becomes `nil_p` and `Point-x` becomes
+/// `Point_x`. Identifiers starting with two underscores are left to temporaries
+/// of the generated code.
+pub fn mangle(name: &str) -> String {
+    let mut ident = String::new();
+
+    for ch in name.chars() {
+        match ch {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' => ident.push(ch),
+            '-' | '/' => ident.push('_'),
+            '?' => ident.push_str("_p"),
+            '!' => ident.push_str("_x"),
+            '*' => ident.push_str("_star"),
+            '+' => ident.push_str("_plus"),
+            '>' => ident.push_str("_to_"),
+            '<' => ident.push_str("_lt"),
+            '=' => ident.push_str("_eq"),
+            other => ident.push_str(&format!("_u{:x}", other as u32)),
+        }
+    }
+
+    if ident.is_empty()
+        || ident == "_"
+        || ident.starts_with("__")
+        || ident.starts_with(|ch: char| ch.is_ascii_digit())
+    {
+        ident.insert(0, 'v');
+    }
+
+    if KEYWORDS.contains(&ident.as_str()) || RESERVED.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+
+    ident
+}
+
+/// Hands out identifiers, numbering them apart when different owners would end
+/// up with the same one.
+#[derive(Debug, Clone, Default)]
+pub struct Names {
+    owners: HashMap<String, String>,
+}
+
+impl Names {
+    pub fn claim(&mut self, ident: String, owner: &str) -> String {
+        let mut candidate = ident.clone();
+        let mut counter = 2;
+
+        loop {
+            match self.owners.get(&candidate) {
+                None => {
+                    self.owners.insert(candidate.clone(), owner.to_string());
+                    return candidate;
+                }
+                Some(existing) if existing == owner => return candidate,
+                Some(_) => {
+                    candidate = format!("{}_{}", ident, counter);
+                    counter += 1;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    codegen::Generated,
+    error::CompileError,
+    project::{generate, package_name, runtime_path, write},
+};
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Compiles the Flop file at `path` into the executable `output`. The generated
+/// project is built offline with the local `cargo` in `build_dir`, by default a
+/// directory under the system temporary directory that later builds reuse.
+pub fn compile(
+    path: &Path,
+    output: &Path,
+    build_dir: Option<&Path>,
+    runtime: Option<&Path>,
+) -> miette::Result<PathBuf> {
+    let generated = generate(path)?;
+    let name = package_name(path, output);
+
+    let project = match build_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => env::temp_dir().join("flop").join(&name),
+    };
+
+    write(&project, &name, &generated.code, &runtime_path(runtime))?;
+
+    let result = Command::new(cargo())
+        .args(["build", "--release", "--offline", "--message-format=short"])
+        .arg("--manifest-path")
+        .arg(project.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(project.join("target"))
+        .output()
+        .map_err(CompileError::from)?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        return Err(rustc_error(&generated, &stderr).into());
+    }
+
+    let binary =
+        project
+            .join("target")
+            .join("release")
+            .join(format!("{}{}", name, env::consts::EXE_SUFFIX));
+
+    fs::copy(&binary, output).map_err(CompileError::from)?;
+
+    Ok(output.to_path_buf())
+}
+
+/// The `cargo` running this program when there is one, so `cargo run -- compile`
+/// uses the same toolchain.
+fn cargo() -> OsString {
+    env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"))
+}
+
+/// Points the first error `rustc` reports in `src/main.rs` at the Flop form the
+/// offending code was generated from. Everything else is reported as cargo's
+/// own output.
+fn rustc_error(generated: &Generated, stderr: &str) -> CompileError {
+    let located = stderr.lines().find_map(|line| {
+        let position = line.strip_prefix("src/main.rs:")?;
+
+        let mut parts = position.splitn(3, ':');
+        let row = parts.next()?.parse().ok()?;
+        let column = parts.next()?.parse().ok()?;
+        let message = parts.next()?.trim().strip_prefix("error")?;
+
+        // drops the error code, `[E0308]: mismatched types`
+        let message = match message.split_once(": ") {
+            Some((_, message)) => message,
+            None => message,
+        };
+
+        let token = generated
+            .source_map
+            .lookup_position(&generated.code, row, column)?;
+
+        Some(CompileError::Rustc {
+            token: token.clone(),
+            message: message.to_string(),
+        })
+    });
+
+    located.unwrap_or_else(|| CompileError::Cargo {
+        output: stderr.trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_map::SourceMap;
+    use flop_frontend::token::{Token, TokenKind};
+
+    fn token(name: &str, offset: usize) -> Token {
+        Token::new(
+            name,
+            TokenKind::Symbol,
+            3,
+            offset,
+            name.len(),
+            Path::new("main.ls"),
+        )
+    }
+
+    /// Line 2 holds a two-byte character before the failing `s`, which rustc
+    /// reports at character column 35 and which starts at byte 35 of the line.
+    fn generated() -> Generated {
+        let code = "fn main() {\n    let s = \"héllo\"; let x: i64 = s;\n}\n".to_string();
+
+        let line = code.find("    let").unwrap();
+        let failing = code.rfind('s').unwrap();
+
+        let mut source_map = SourceMap::default();
+        source_map.record(line, code.rfind(';').unwrap(), &token("setq", 1));
+        source_map.record(failing, failing + 1, &token("s", 7));
+
+        Generated { code, source_map }
+    }
+
+    #[test]
+    fn rustc_errors_point_at_the_flop_form() {
+        let stderr = "   Compiling main v0.1.0\n\
+                      src/main.rs:2:35: error[E0308]: mismatched types\n\
+                      error: could not compile `main`";
+
+        match rustc_error(&generated(), stderr) {
+            CompileError::Rustc { token, message } => {
+                assert_eq!((token.token.as_str(), token.offset), ("s", 7));
+                assert_eq!(message, "mismatched types");
+            }
+            other => panic!("expected a rustc error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn other_cargo_failures_keep_the_output() {
+        let stderr = "error: no matching package named `flop-runtime` found";
+
+        match rustc_error(&generated(), stderr) {
+            CompileError::Cargo { output } => assert_eq!(output, stderr),
+            other => panic!("expected cargo's output, found {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    codegen::{transpile, Generated},
+    error::CompileError,
+};
+use flop_frontend::{lexer::tokenise, parser::parse};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The runtime crate of this checkout, generated projects depend on it by path.
+pub const RUNTIME_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../flop-runtime");
+
+/// Compiles the Flop file at `path` into a Cargo project in `output`, returning
+/// the path of the generated `main.rs`. `runtime` overrides where the project
+/// finds `flop-runtime`.
+pub fn build(path: &Path, output: &Path, runtime: Option<&Path>) -> miette::Result<PathBuf> {
+    let generated = generate(path)?;
+
+    Ok(write(
+        output,
+        &package_name(path, output),
+        &generated.code,
+        &runtime_path(runtime),
+    )?)
+}
+
+/// Reads, parses and transpiles the Flop file at `path`.
+pub fn generate(path: &Path) -> miette::Result<Generated> {
+    let source = fs::read_to_string(path).map_err(CompileError::from)?;
+
+    let mut tokens = tokenise(&source, path)?;
+    let nodes = parse(&mut tokens)?;
+
+    Ok(transpile(&nodes, path)?)
+}
+
+pub(crate) fn runtime_path(runtime: Option<&Path>) -> PathBuf {
+    match runtime {
+        Some(runtime) => runtime.to_path_buf(),
+        None => PathBuf::from(RUNTIME_PATH),
+    }
+}
+
+/// Writes `Cargo.toml` and `src/main.rs`, leaving anything else in `output` alone
+/// so rebuilds reuse the `target` directory.
+pub fn write(
+    output: &Path,
+    name: &str,
+    code: &str,
+    runtime: &Path,
+) -> Result<PathBuf, CompileError> {
+    let runtime = fs::canonicalize(runtime).unwrap_or_else(|_| runtime.to_path_buf());
+
+    let manifest = format!(
+        "[package]\n\
+         name = {name:?}\n\
+         version = \"0.1.0\"\n\
+         edition = \"2021\"\n\
+         \n\
+         [dependencies]\n\
+         flop-runtime = {{ path = {runtime:?} }}\n\
+         \n\
+         # Keeps the project out of any workspace it is generated in.\n\
+         [workspace]\n",
+        name = name,
+        runtime = runtime.display().to_string(),
+    );
+
+    let src = output.join("src");
+    fs::create_dir_all(&src)?;
+    fs::write(output.join("Cargo.toml"), manifest)?;
+
+    let main = src.join("main.rs");
+    fs::write(&main, code)?;
+
+    Ok(main)
+}
+
+/// The package name of the project, taken from the output directory or the
+/// source file and reduced to the characters Cargo accepts.
+pub fn package_name(path: &Path, output: &Path) -> String {
+    let stem = output
+        .file_name()
+        .or_else(|| path.file_stem())
+        .and_then(|name| name.to_str())
+        .unwrap_or("flop-program");
+
+    let name = stem
+        .chars()
+        .map(|ch| match ch {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => ch,
+            _ => '-',
+        })
+        .collect::<String>();
+
+    match name.chars().next() {
+        Some(ch) if ch.is_ascii_alphabetic() => name,
+        _ => format!("flop-{}", name),
+    }
+}
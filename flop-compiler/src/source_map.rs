@@ -0,0 +1,55 @@
+use flop_frontend::token::Token;
+
+/// A stretch of generated code and the Flop token it was generated for.
+#[derive(Debug, Clone)]
+struct Span {
+    start: usize,
+    end: usize,
+    token: Token,
+}
+
+/// Relates the generated Rust code back to the Flop source, so errors the Rust
+/// compiler reports can point at the form they come from.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    spans: Vec<Span>,
+}
+
+impl SourceMap {
+    /// Records that the bytes `start..end` of the generated code come from `token`.
+    pub fn record(&mut self, start: usize, end: usize, token: &Token) {
+        self.spans.push(Span {
+            start,
+            end,
+            token: token.clone(),
+        });
+    }
+
+    /// The token of the innermost form covering the byte `offset`.
+    pub fn lookup(&self, offset: usize) -> Option<&Token> {
+        self.spans
+            .iter()
+            .filter(|span| span.start <= offset && offset < span.end)
+            .min_by_key(|span| span.end - span.start)
+            .map(|span| &span.token)
+    }
+
+    /// Like [`SourceMap::lookup`] with the 1-based line and column, counted in
+    /// characters, that `rustc` reports positions in.
+    pub fn lookup_position(&self, code: &str, line: usize, column: usize) -> Option<&Token> {
+        let line_start = code
+            .split_inclusive('\n')
+            .take(line.checked_sub(1)?)
+            .map(str::len)
+            .sum::<usize>();
+
+        let line_text = code[line_start..].lines().next()?;
+
+        let offset = line_text
+            .char_indices()
+            .nth(column.checked_sub(1)?)
+            .map_or(line_text.len(), |(index, _)| index);
+
+        self.lookup(line_start + offset)
+    }
+}
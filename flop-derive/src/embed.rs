@@ -0,0 +1,165 @@
+use crate::lift::{Lift, Lifter};
+use flop_frontend::{lexer::tokenise, parser::parse, token::Token};
+use miette::Diagnostic;
+use proc_macro::{Delimiter, Span, TokenStream, TokenTree};
+use std::path::Path;
+
+/// Where a Rust token was placed in the text handed to the Flop lexer.
+struct Placed {
+    row: usize,
+    start: usize,
+    span: Span,
+}
+
+/// Rebuilds Flop source from the tokens Rust split it into, keeping every token in
+/// the row and column it was written at, so positions the Flop lexer reports can
+/// be traced back to the Rust token.
+#[derive(Default)]
+struct Source {
+    text: String,
+    first_line: Option<usize>,
+    row: usize,
+    column: usize,
+    placed: Vec<Placed>,
+}
+
+impl Source {
+    fn push_stream(&mut self, stream: TokenStream) {
+        for tree in stream {
+            match tree {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::None => ("", ""),
+                    };
+
+                    self.push(open, group.span_open());
+                    self.push_stream(group.stream());
+                    self.push(close, group.span_close());
+                }
+                TokenTree::Ident(ident) => self.push(&ident.to_string(), ident.span()),
+                TokenTree::Punct(punct) => self.push(&punct.to_string(), punct.span()),
+                TokenTree::Literal(literal) => self.push(&literal.to_string(), literal.span()),
+            }
+        }
+    }
+
+    fn push(&mut self, text: &str, span: Span) {
+        if text.is_empty() {
+            return;
+        }
+
+        let first_line = *self.first_line.get_or_insert(span.line());
+        let row = span.line().saturating_sub(first_line);
+        let column = span.column().saturating_sub(1);
+
+        while self.row < row {
+            self.text.push('\n');
+            self.row += 1;
+            self.column = 0;
+        }
+
+        // tokens written next to each other stay together, `nil?` must not become `nil ?`
+        while self.column < column {
+            self.text.push(' ');
+            self.column += 1;
+        }
+
+        self.placed.push(Placed {
+            row: self.row,
+            start: self.column,
+            span,
+        });
+
+        for ch in text.chars() {
+            if ch == '\n' {
+                self.row += 1;
+                self.column = 0;
+            } else {
+                self.column += 1;
+            }
+
+            self.text.push(ch);
+        }
+    }
+
+    /// The Rust token covering the position of `token`, the lexer counts columns
+    /// from one.
+    fn span_of(&self, token: &Token) -> Span {
+        let column = token.offset.saturating_sub(1);
+
+        self.placed
+            .iter()
+            .rev()
+            .find(|placed| {
+                placed.row < token.row || (placed.row == token.row && placed.start <= column)
+            })
+            .map_or_else(Span::call_site, |placed| placed.span)
+    }
+
+    /// The row of the Rust file the rebuilt text starts at, counted from zero like
+    /// the lexer does.
+    fn line_offset(&self) -> usize {
+        self.first_line.map_or(0, |line| line.saturating_sub(1))
+    }
+}
+
+pub fn expand(input: TokenStream) -> TokenStream {
+    let mut source = Source::default();
+    source.push_stream(input);
+
+    // the path of the Rust file is filled in by `file!()` in the expansion
+    let namespace = Path::new("flop!");
+
+    let mut tokens = match tokenise(&source.text, namespace) {
+        Ok(tokens) => tokens,
+        Err(err) => return error(source.span_of(err.token()), &err),
+    };
+
+    let nodes = match parse(&mut tokens) {
+        Ok(nodes) => nodes,
+        Err(err) => {
+            let span = err
+                .token()
+                .map_or_else(Span::call_site, |token| source.span_of(token));
+
+            return error(span, &err);
+        }
+    };
+
+    let lifter = Lifter {
+        row_offset: source.line_offset(),
+    };
+
+    let nodes = nodes.lift(&lifter);
+
+    quote::quote! {
+        {
+            let namespace = ::std::path::PathBuf::from(file!());
+            #nodes
+        }
+    }
+    .into()
+}
+
+/// A Rust compile error at `span` describing a Flop lexer or parser error.
+fn error(span: Span, err: &dyn Diagnostic) -> TokenStream {
+    let labels = err
+        .labels()
+        .into_iter()
+        .flatten()
+        .filter_map(|label| label.label().map(str::to_string))
+        .collect::<Vec<_>>();
+
+    let message = if labels.is_empty() {
+        format!("flop syntax error: {}", err)
+    } else {
+        format!("flop syntax error: {}", labels.join(", "))
+    };
+
+    syn::Error::new(span.into(), message)
+        .into_compile_error()
+        .into()
+}
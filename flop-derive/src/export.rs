@@ -0,0 +1,101 @@
+use crate::flop_name;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Error, FnArg, ItemFn, LitStr, Type};
+
+pub fn expand(attr: TokenStream, item: ItemFn) -> syn::Result<TokenStream> {
+    let mut name = None;
+
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            Err(meta.error("expected `name = \"...\"`"))
+        }
+    });
+
+    syn::parse::Parser::parse2(parser, attr)?;
+
+    let signature = &item.sig;
+
+    if let Some(token) = signature.asyncness {
+        return Err(Error::new(
+            token.span(),
+            "async functions cannot be exported to Flop",
+        ));
+    }
+
+    if !signature.generics.params.is_empty() {
+        return Err(Error::new(
+            signature.generics.span(),
+            "generic functions cannot be exported to Flop",
+        ));
+    }
+
+    for input in &signature.inputs {
+        match input {
+            FnArg::Receiver(receiver) => {
+                return Err(Error::new(
+                    receiver.span(),
+                    "methods cannot be exported to Flop, export a free function instead",
+                ))
+            }
+            FnArg::Typed(arg) if matches!(*arg.ty, Type::Reference(_)) => {
+                return Err(Error::new(
+                    arg.ty.span(),
+                    "exported functions take their arguments by value, like `String` for `&str`",
+                ))
+            }
+            FnArg::Typed(_) => {}
+        }
+    }
+
+    let ident = &signature.ident;
+    let vis = &item.vis;
+    let name = name.unwrap_or_else(|| flop_name(ident));
+    let arity = signature.inputs.len();
+    let args = (0..arity)
+        .map(|index| format_ident!("arg{}", index))
+        .collect::<Vec<_>>();
+    let indices = 0..arity;
+
+    Ok(quote! {
+        #item
+
+        #[doc = concat!("Registration of `", stringify!(#ident), "` as the Flop function `", #name, "`.")]
+        #[allow(non_camel_case_types)]
+        #vis struct #ident {}
+
+        impl #ident {
+            /// The name Flop calls the function by.
+            pub const NAME: &'static str = #name;
+
+            /// Makes the function callable from Flop code evaluated in `env`.
+            pub fn register(env: &mut ::flop_interpretor::env::Environment) {
+                use ::flop_interpretor::{
+                    env::Environment,
+                    error::EvalError,
+                    flop_frontend::token::Token,
+                    native::{convert, IntoResult},
+                    stdlib::expect_arity,
+                    value::Value,
+                };
+
+                fn call(
+                    _env: &mut Environment,
+                    token: &Token,
+                    args: Vec<Value>,
+                ) -> Result<Value, EvalError> {
+                    expect_arity(token, &args, #arity)?;
+
+                    #(let #args = convert(&args[#indices], token)?;)*
+
+                    IntoResult::into_result(#ident(#(#args),*)).map_err(|err| err.at(token))
+                }
+
+                env.register_fn(Self::NAME, call);
+            }
+        }
+    })
+}
@@ -0,0 +1,137 @@
+//! Macros generating the glue between Rust code and Flop, re-exported by
+//! `flop_interpretor`.
+
+mod embed;
+mod export;
+mod lift;
+mod value;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemFn};
+
+/// Exposes a Rust function to Flop. Next to the function, an empty struct of the
+/// same name is generated, holding the Flop name as `NAME` and a `register` function
+/// adding it to an environment. Arguments are converted with `FromValue`, the result, or the
+/// value of an `Ok`, with `IntoValue`, and an `Err` is reported as a native error.
+///
+/// The Flop name is the Rust one with dashes for underscores, unless given with
+/// `#[flop_export(name = "...")]`.
+///
+/// ```
+/// use flop_interpretor::{flop_export, interpreter::Interpreter};
+///
+/// #[flop_export]
+/// fn parse_port(text: String) -> Result<i64, std::num::ParseIntError> {
+///     text.parse()
+/// }
+///
+/// #[flop_export(name = "clamp")]
+/// fn clamp_between(low: i64, high: i64, value: i64) -> i64 {
+///     value.clamp(low, high)
+/// }
+///
+/// let mut interpreter = Interpreter::new();
+/// parse_port::register(interpreter.env_mut());
+/// clamp_between::register(interpreter.env_mut());
+///
+/// let port = interpreter.eval_str(r#"(clamp 1 1024 (parse-port "8080"))"#).unwrap();
+/// assert_eq!(port.extract::<i64>(), Some(1024));
+///
+/// let err = interpreter.eval_str(r#"(parse-port "http")"#).unwrap_err();
+/// assert_eq!(err.kind(), "native-error");
+/// ```
+#[proc_macro_attribute]
+pub fn flop_export(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemFn);
+
+    export::expand(attr.into(), item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `FromValue` and `IntoValue` for a struct or an enum. Structs become
+/// Flop structs of the same name, enum variants become structs belonging to an
+/// enum, as with `defenum`. Fields keep their names with dashes for underscores,
+/// tuple fields are named by their position.
+///
+/// ```
+/// use flop_interpretor::{interpreter::Interpreter, value::Value, FlopValue};
+///
+/// #[derive(Debug, PartialEq, FlopValue)]
+/// struct Point {
+///     x: i64,
+///     y: i64,
+/// }
+///
+/// #[derive(Debug, PartialEq, FlopValue)]
+/// enum Shape {
+///     Circle { center: Point, radius: f64 },
+///     Square(Point, f64),
+///     Empty,
+/// }
+///
+/// let mut interpreter = Interpreter::new();
+/// interpreter.set_global("origin", Point { x: 0, y: 0 });
+///
+/// interpreter
+///     .eval_str("(defenum Shape (Circle center radius) (Empty))")
+///     .unwrap();
+///
+/// let circle = interpreter.eval_str("(Circle origin 2.5)").unwrap();
+/// assert_eq!(
+///     circle.extract::<Shape>(),
+///     Some(Shape::Circle { center: Point { x: 0, y: 0 }, radius: 2.5 })
+/// );
+///
+/// interpreter.set_global("shape", Shape::Empty);
+/// let empty = interpreter.eval_str("(and (Shape? shape) (Empty? shape))").unwrap();
+/// assert_eq!(empty, Value::Bool(true));
+///
+/// interpreter.set_global("square", Shape::Square(Point { x: 1, y: 2 }, 3.0));
+/// let square = interpreter.eval_str("square").unwrap();
+/// assert_eq!(square.extract(), Some(Shape::Square(Point { x: 1, y: 2 }, 3.0)));
+/// ```
+#[proc_macro_derive(FlopValue)]
+pub fn derive_flop_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    value::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Parses Flop code while Rust compiles, expanding to the parsed forms as a
+/// `Stack<Node>` ready for `Interpreter::eval_nodes`. Syntax errors are compile
+/// errors at the offending token. Rust splits the code into tokens first, so
+/// comments are written the Rust way, with `//`.
+///
+/// ```
+/// use flop_interpretor::{flop, interpreter::Interpreter, value::Value};
+///
+/// let mut interpreter = Interpreter::new();
+///
+/// let value = interpreter
+///     .eval_nodes(flop! {
+///         (defn add [x y] "Adds two numbers" (+ x y))
+///         // tokens written together stay together
+///         (if (nil? nil) (add 1 2) 0)
+///     })
+///     .unwrap();
+///
+/// assert_eq!(value, Value::Integer(3));
+/// ```
+///
+/// ```compile_fail
+/// use flop_interpretor::flop;
+///
+/// let nodes = flop! { (defn add [x y) };
+/// ```
+#[proc_macro]
+pub fn flop(input: TokenStream) -> TokenStream {
+    embed::expand(input)
+}
+
+/// The Flop spelling of a Rust identifier.
+fn flop_name(ident: &syn::Ident) -> String {
+    ident.to_string().trim_start_matches("r#").replace('_', "-")
+}
@@ -0,0 +1,223 @@
+use flop_frontend::{
+    ast::{
+        Arity, Catch, Conditional, Documentation, EnumDefinition, FunctionCall, FunctionDefinition,
+        HandlerBind, Iteration, Lambda, List, Logical, Loop, Map, Match, MatchClause,
+        MethodDefinition, MultiDefinition, Namespace, Node, Parameters, Pattern, Recur, Require,
+        RestartCase, RestartClause, Set, StructDefinition, Try, VariableCall, VariableDefinition,
+        While,
+    },
+    stack::Stack,
+    token::Token,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Settings shared while turning a parsed AST into Rust code building it again.
+pub struct Lifter {
+    /// Added to token rows, so they count rows of the Rust file.
+    pub row_offset: usize,
+}
+
+/// Parsed Flop that can be written out as a Rust expression rebuilding it. The
+/// expression expects the path of the source file in a `namespace` binding.
+pub trait Lift {
+    fn lift(&self, lifter: &Lifter) -> TokenStream;
+}
+
+const AST: &str = "::flop_interpretor::flop_frontend::ast";
+
+fn ast(name: &str) -> TokenStream {
+    format!("{}::{}", AST, name).parse().unwrap()
+}
+
+impl Lift for Token {
+    fn lift(&self, lifter: &Lifter) -> TokenStream {
+        let text = &self.token;
+        let kind = format_ident!("{}", format!("{:?}", self.token_kind));
+        let row = self.row + lifter.row_offset;
+        let offset = self.offset;
+        let length = self.length;
+
+        quote! {
+            ::flop_interpretor::flop_frontend::token::Token::new(
+                #text,
+                ::flop_interpretor::flop_frontend::token::TokenKind::#kind,
+                #row,
+                #offset,
+                #length,
+                &namespace,
+            )
+        }
+    }
+}
+
+impl Lift for bool {
+    fn lift(&self, _lifter: &Lifter) -> TokenStream {
+        quote!(#self)
+    }
+}
+
+impl<T: Lift> Lift for Stack<T> {
+    fn lift(&self, lifter: &Lifter) -> TokenStream {
+        let items = self.data.iter().map(|item| item.lift(lifter));
+
+        quote! {
+            ::flop_interpretor::flop_frontend::stack::Stack {
+                data: ::std::collections::VecDeque::from(vec![#(#items),*]),
+            }
+        }
+    }
+}
+
+impl<T: Lift> Lift for Option<T> {
+    fn lift(&self, lifter: &Lifter) -> TokenStream {
+        match self {
+            Some(item) => {
+                let item = item.lift(lifter);
+                quote!(Some(#item))
+            }
+            None => quote!(None),
+        }
+    }
+}
+
+impl<T: Lift> Lift for Box<T> {
+    fn lift(&self, lifter: &Lifter) -> TokenStream {
+        let item = (**self).lift(lifter);
+
+        quote!(Box::new(#item))
+    }
+}
+
+impl<A: Lift, B: Lift> Lift for (A, B) {
+    fn lift(&self, lifter: &Lifter) -> TokenStream {
+        let first = self.0.lift(lifter);
+        let second = self.1.lift(lifter);
+
+        quote!((#first, #second))
+    }
+}
+
+/// Lifts a struct field by field.
+macro_rules! lift_struct {
+    ($($name:ident { $($field:ident),* })*) => {
+        $(
+            impl Lift for $name {
+                fn lift(&self, lifter: &Lifter) -> TokenStream {
+                    let path = ast(stringify!($name));
+                    $(let $field = self.$field.lift(lifter);)*
+
+                    quote!(#path { $($field: #$field),* })
+                }
+            }
+        )*
+    };
+}
+
+lift_struct! {
+    FunctionDefinition { name, docstrings, arities, private }
+    Lambda { token, arities }
+    Arity { parameters, body }
+    Parameters { required, optional, rest, keywords }
+    Conditional { condition, true_expression, false_expression }
+    Match { token, value, clauses }
+    MatchClause { pattern, guard, body }
+    Try { token, body, catch, finally }
+    Catch { name, body }
+    HandlerBind { token, handlers, body }
+    RestartCase { token, body, restarts }
+    RestartClause { name, arity }
+    Logical { operator, operands }
+    StructDefinition { name, fields }
+    EnumDefinition { name, variants }
+    MultiDefinition { name, dispatch }
+    MethodDefinition { name, value, arities }
+    Namespace { token, name }
+    Require { token, module, alias }
+    While { token, condition, body }
+    Loop { token, names, values, body }
+    Recur { token, arguments }
+    Iteration { token, name, source, body }
+    VariableDefinition { name, assignment }
+    FunctionCall { name, arguments }
+    VariableCall { name }
+    List { data }
+    Map { data }
+    Set { data }
+    Documentation { commentary, code }
+}
+
+impl Lift for Pattern {
+    fn lift(&self, lifter: &Lifter) -> TokenStream {
+        let path = ast("Pattern");
+
+        match self {
+            Pattern::Wildcard(token) => {
+                let token = token.lift(lifter);
+                quote!(#path::Wildcard(#token))
+            }
+            Pattern::Literal(token) => {
+                let token = token.lift(lifter);
+                quote!(#path::Literal(#token))
+            }
+            Pattern::Bind(token) => {
+                let token = token.lift(lifter);
+                quote!(#path::Bind(#token))
+            }
+            Pattern::List { items, rest } => {
+                let items = items.lift(lifter);
+                let rest = rest.lift(lifter);
+                quote!(#path::List { items: #items, rest: #rest })
+            }
+            Pattern::Constructor { name, fields, rest } => {
+                let name = name.lift(lifter);
+                let fields = fields.lift(lifter);
+                let rest = rest.lift(lifter);
+                quote!(#path::Constructor { name: #name, fields: #fields, rest: #rest })
+            }
+            Pattern::Map { entries } => {
+                let entries = entries.lift(lifter);
+                quote!(#path::Map { entries: #entries })
+            }
+        }
+    }
+}
+
+impl Lift for Node {
+    fn lift(&self, lifter: &Lifter) -> TokenStream {
+        let (variant, inner) = match self {
+            Node::FunctionDefinition(inner) => ("FunctionDefinition", inner.lift(lifter)),
+            Node::StructDefinition(inner) => ("StructDefinition", inner.lift(lifter)),
+            Node::EnumDefinition(inner) => ("EnumDefinition", inner.lift(lifter)),
+            Node::MultiDefinition(inner) => ("MultiDefinition", inner.lift(lifter)),
+            Node::MethodDefinition(inner) => ("MethodDefinition", inner.lift(lifter)),
+            Node::Namespace(inner) => ("Namespace", inner.lift(lifter)),
+            Node::Require(inner) => ("Require", inner.lift(lifter)),
+            Node::Lambda(inner) => ("Lambda", inner.lift(lifter)),
+            Node::Conditional(inner) => ("Conditional", inner.lift(lifter)),
+            Node::Match(inner) => ("Match", inner.lift(lifter)),
+            Node::Try(inner) => ("Try", inner.lift(lifter)),
+            Node::HandlerBind(inner) => ("HandlerBind", inner.lift(lifter)),
+            Node::RestartCase(inner) => ("RestartCase", inner.lift(lifter)),
+            Node::Logical(inner) => ("Logical", inner.lift(lifter)),
+            Node::While(inner) => ("While", inner.lift(lifter)),
+            Node::Loop(inner) => ("Loop", inner.lift(lifter)),
+            Node::Recur(inner) => ("Recur", inner.lift(lifter)),
+            Node::DoTimes(inner) => ("DoTimes", inner.lift(lifter)),
+            Node::DoSeq(inner) => ("DoSeq", inner.lift(lifter)),
+            Node::VariableDefinition(inner) => ("VariableDefinition", inner.lift(lifter)),
+            Node::FunctionCall(inner) => ("FunctionCall", inner.lift(lifter)),
+            Node::Literal(inner) => ("Literal", inner.lift(lifter)),
+            Node::VariableCall(inner) => ("VariableCall", inner.lift(lifter)),
+            Node::List(inner) => ("List", inner.lift(lifter)),
+            Node::Map(inner) => ("Map", inner.lift(lifter)),
+            Node::Set(inner) => ("Set", inner.lift(lifter)),
+            Node::Documentation(inner) => ("Documentation", inner.lift(lifter)),
+        };
+
+        let path = ast("Node");
+        let variant = format_ident!("{}", variant);
+
+        quote!(#path::#variant(#inner))
+    }
+}
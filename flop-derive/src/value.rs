@@ -0,0 +1,165 @@
+use crate::flop_name;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, Ident};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "FlopValue cannot be derived for generic types",
+        ));
+    }
+
+    let ident = &input.ident;
+    let name = ident.to_string();
+
+    let (into_value, from_value) = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, build) = build(quote!(Self), &name, None, &data.fields);
+            let convert = convert(quote!(Self), &name, &data.fields);
+
+            (
+                quote! {
+                    let #pattern = self;
+                    #build
+                },
+                quote! {
+                    #convert
+                    None
+                },
+            )
+        }
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            let mut converts = Vec::new();
+
+            for variant in &data.variants {
+                let variant_ident = &variant.ident;
+                let variant_name = variant_ident.to_string();
+
+                let (pattern, build) = build(
+                    quote!(Self::#variant_ident),
+                    &variant_name,
+                    Some(&name),
+                    &variant.fields,
+                );
+
+                arms.push(quote!(#pattern => #build));
+                converts.push(convert(
+                    quote!(Self::#variant_ident),
+                    &variant_name,
+                    &variant.fields,
+                ));
+            }
+
+            (
+                quote! {
+                    match self {
+                        #(#arms,)*
+                    }
+                },
+                quote! {
+                    #(#converts)*
+                    None
+                },
+            )
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                "FlopValue cannot be derived for unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl ::flop_interpretor::native::IntoValue for #ident {
+            fn into_value(self) -> ::flop_interpretor::value::Value {
+                #into_value
+            }
+        }
+
+        impl ::flop_interpretor::native::FromValue for #ident {
+            const EXPECTED: &'static str = #name;
+
+            fn from_value(value: &::flop_interpretor::value::Value) -> Option<Self> {
+                #from_value
+            }
+        }
+    })
+}
+
+/// Names of the fields in Flop and the bindings holding their values.
+fn fields(fields: &Fields) -> (Vec<String>, Vec<Ident>) {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => (flop_name(ident), format_ident!("field_{}", ident)),
+            None => (index.to_string(), format_ident!("field_{}", index)),
+        })
+        .unzip()
+}
+
+/// A pattern destructuring `path` and the expression building its Flop value.
+fn build(
+    path: TokenStream,
+    name: &str,
+    enumeration: Option<&str>,
+    fields: &Fields,
+) -> (TokenStream, TokenStream) {
+    let (names, bindings) = self::fields(fields);
+
+    let pattern = match fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#idents: #bindings),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => path,
+    };
+
+    let enumeration = match enumeration {
+        Some(enumeration) => quote!(Some(#enumeration)),
+        None => quote!(None),
+    };
+
+    let build = quote! {
+        ::flop_interpretor::structure::StructValue::build(
+            #name,
+            #enumeration,
+            &[#(#names),*],
+            vec![#(::flop_interpretor::native::IntoValue::into_value(#bindings)),*],
+        )
+    };
+
+    (pattern, build)
+}
+
+/// Returns `path` built from `value` when it is an instance of `name`.
+fn convert(path: TokenStream, name: &str, fields: &Fields) -> TokenStream {
+    let (names, _) = self::fields(fields);
+
+    let values = names.iter().map(|field| {
+        quote! {
+            ::flop_interpretor::native::FromValue::from_value(instance.get(#field)?)?
+        }
+    });
+
+    let construct = match fields {
+        Fields::Named(named) => {
+            let idents = named.named.iter().map(|field| &field.ident);
+            quote!(#path { #(#idents: #values),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#values),*)),
+        Fields::Unit => path,
+    };
+
+    quote! {
+        if let Some(instance) = ::flop_interpretor::structure::StructValue::of(value, #name) {
+            let _ = instance;
+            return Some(#construct);
+        }
+    }
+}
@@ -3,22 +3,219 @@ use crate::{stack::Stack, token::Token};
 #[derive(Debug, Clone)]
 pub struct FunctionDefinition {
     pub name: Token,
-    pub parameters: Stack<Token>,
     pub docstrings: Token,
+    pub arities: Stack<Arity>,
+    /// Declared with `defn-`, only reachable from its own module.
+    pub private: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub token: Token,
+    pub arities: Stack<Arity>,
+}
+
+/// One parameter list of a function together with the body it runs.
+#[derive(Debug, Clone)]
+pub struct Arity {
+    pub parameters: Parameters,
     pub body: Stack<Node>,
 }
 
+/// `[pattern... &optional name... & rest]`, or `&key name...` in place of the
+/// rest section. Optional arguments are taken by position before any keyword
+/// ones. Optional and keyword parameters are either a symbol or `[name default]`.
+#[derive(Debug, Clone, Default)]
+pub struct Parameters {
+    pub required: Stack<Pattern>,
+    pub optional: Stack<(Token, Option<Node>)>,
+    pub rest: Option<Pattern>,
+    pub keywords: Stack<(Token, Option<Node>)>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Conditional {
-    pub condition: Stack<FunctionCall>,
+    pub condition: Box<Node>,
     pub true_expression: Stack<Node>,
     pub false_expression: Stack<Node>,
 }
 
+/// The builtin `=` under a name no symbol can spell. A lowered `case` compares
+/// its key with it, so a user definition of `=` leaves `case` alone.
+pub const CASE_EQUAL: &str = "case =";
+
+/// `(match value [pattern body...] ...)`, clauses are tried in order.
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub token: Token,
+    pub value: Box<Node>,
+    pub clauses: Stack<MatchClause>,
+}
+
+/// `[pattern body...]` or `[pattern :when guard body...]`
+#[derive(Debug, Clone)]
+pub struct MatchClause {
+    pub pattern: Pattern,
+    pub guard: Option<Box<Node>>,
+    pub body: Stack<Node>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `_` matches anything without binding it.
+    Wildcard(Token),
+    Literal(Token),
+    /// A symbol matches anything and binds it to the name.
+    Bind(Token),
+    /// `[a b & rest]`, without `& rest` the lengths must be equal.
+    List {
+        items: Stack<Pattern>,
+        rest: Option<Box<Pattern>>,
+    },
+    /// `(Name field...)` matches a struct of that type field by field. When `Name`
+    /// is not a type it is a list pattern whose first item binds `Name`.
+    Constructor {
+        name: Token,
+        fields: Stack<Pattern>,
+        rest: Option<Box<Pattern>>,
+    },
+    /// `{key pattern ...}` matches maps holding every key.
+    Map {
+        entries: Stack<(Token, Pattern)>,
+    },
+}
+
+/// `(try body... (catch name handler...) (finally cleanup...))`, both clauses optional.
+#[derive(Debug, Clone)]
+pub struct Try {
+    pub token: Token,
+    pub body: Stack<Node>,
+    pub catch: Option<Catch>,
+    pub finally: Stack<Node>,
+}
+
+/// Binds the caught error to `name` while the handler runs.
+#[derive(Debug, Clone)]
+pub struct Catch {
+    pub name: Token,
+    pub body: Stack<Node>,
+}
+
+/// `(handler-bind [type handler ...] body...)`, handlers run where an error is
+/// signalled, before anything unwinds.
+#[derive(Debug, Clone)]
+pub struct HandlerBind {
+    pub token: Token,
+    /// Alternating condition types and handler functions.
+    pub handlers: Stack<Node>,
+    pub body: Stack<Node>,
+}
+
+/// `(restart-case expression (name [params] body...) ...)`
+#[derive(Debug, Clone)]
+pub struct RestartCase {
+    pub token: Token,
+    pub body: Box<Node>,
+    pub restarts: Stack<RestartClause>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RestartClause {
+    pub name: Token,
+    pub arity: Arity,
+}
+
+/// `and` / `or`, evaluated left to right until the result is decided.
+#[derive(Debug, Clone)]
+pub struct Logical {
+    pub operator: Token,
+    pub operands: Stack<Node>,
+}
+
+/// `(defstruct Name "doc" field...)`, the docstring is optional.
+#[derive(Debug, Clone)]
+pub struct StructDefinition {
+    pub name: Token,
+    pub fields: Stack<Token>,
+}
+
+/// `(defenum Name "doc" (Variant field...)...)`, the docstring is optional. Each
+/// variant is a struct type of its own.
+#[derive(Debug, Clone)]
+pub struct EnumDefinition {
+    pub name: Token,
+    pub variants: Stack<StructDefinition>,
+}
+
+/// `(defmulti name "doc" dispatch)`, the docstring is optional. A call applies
+/// `dispatch` to the arguments and runs the method registered for the result.
+#[derive(Debug, Clone)]
+pub struct MultiDefinition {
+    pub name: Token,
+    pub dispatch: Box<Node>,
+}
+
+/// `(defmethod name value [params] body...)`, or with several arities like `fn`.
+#[derive(Debug, Clone)]
+pub struct MethodDefinition {
+    pub name: Token,
+    pub value: Box<Node>,
+    pub arities: Stack<Arity>,
+}
+
+/// `(ns name "doc")`, the docstring is optional. Global definitions after it in
+/// the same file belong to the module `name`.
+#[derive(Debug, Clone)]
+pub struct Namespace {
+    pub token: Token,
+    pub name: Token,
+}
+
+/// `(require name)` or `(require name :as alias)`
+#[derive(Debug, Clone)]
+pub struct Require {
+    pub token: Token,
+    pub module: Token,
+    pub alias: Option<Token>,
+}
+
+/// `(while condition body...)`
+#[derive(Debug, Clone)]
+pub struct While {
+    pub token: Token,
+    pub condition: Box<Node>,
+    pub body: Stack<Node>,
+}
+
+/// `(loop [name value ...] body...)`, a `recur` in tail position of the body
+/// rebinds the names and runs the body again.
+#[derive(Debug, Clone)]
+pub struct Loop {
+    pub token: Token,
+    pub names: Stack<Token>,
+    pub values: Stack<Node>,
+    pub body: Stack<Node>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Recur {
+    pub token: Token,
+    pub arguments: Stack<Node>,
+}
+
+/// `(dotimes [name count] body...)` and `(doseq [name list] body...)`
+#[derive(Debug, Clone)]
+pub struct Iteration {
+    pub token: Token,
+    pub name: Token,
+    pub source: Box<Node>,
+    pub body: Stack<Node>,
+}
+
 #[derive(Debug, Clone)]
 pub struct VariableDefinition {
     pub name: Token,
-    pub assignment: Token,
+    pub assignment: Box<Node>,
 }
 
 #[derive(Debug, Clone)]
@@ -37,6 +234,17 @@ pub struct List {
     pub data: Stack<Node>,
 }
 
+/// `{key value ...}`, entries are stored as alternating keys and values.
+#[derive(Debug, Clone)]
+pub struct Map {
+    pub data: Stack<Node>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Set {
+    pub data: Stack<Node>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Documentation {
     pub commentary: Token,
@@ -46,11 +254,30 @@ pub struct Documentation {
 #[derive(Debug, Clone)]
 pub enum Node {
     FunctionDefinition(FunctionDefinition),
+    StructDefinition(StructDefinition),
+    EnumDefinition(EnumDefinition),
+    MultiDefinition(MultiDefinition),
+    MethodDefinition(MethodDefinition),
+    Namespace(Namespace),
+    Require(Require),
+    Lambda(Lambda),
     Conditional(Conditional),
+    Match(Match),
+    Try(Try),
+    HandlerBind(HandlerBind),
+    RestartCase(RestartCase),
+    Logical(Logical),
+    While(While),
+    Loop(Loop),
+    Recur(Recur),
+    DoTimes(Iteration),
+    DoSeq(Iteration),
     VariableDefinition(VariableDefinition),
     FunctionCall(FunctionCall),
     Literal(Token),
     VariableCall(VariableCall),
     List(List),
+    Map(Map),
+    Set(Set),
     Documentation(Documentation),
 }
@@ -0,0 +1,303 @@
+use crate::{
+    ast::{Arity, EnumDefinition, Match, Node, Pattern},
+    error::ParseWarning,
+    stack::Stack,
+    token::Token,
+};
+use std::collections::HashMap;
+
+/// Looks for code that parses but is likely a mistake. It remembers the enums
+/// defined so far, so one checker should see every form of a program in order.
+#[derive(Debug, Clone, Default)]
+pub struct Checker {
+    /// Variant names and field counts of every `defenum`, by enum name.
+    enums: HashMap<String, Vec<(String, usize)>>,
+    /// The enum each variant belongs to.
+    variants: HashMap<String, String>,
+}
+
+impl Checker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a warning for each likely mistake in `node`.
+    pub fn check(&mut self, node: &Node) -> Vec<ParseWarning> {
+        let mut warnings = Vec::new();
+
+        self.check_node(node, &mut warnings);
+
+        warnings
+    }
+
+    fn check_node(&mut self, node: &Node, warnings: &mut Vec<ParseWarning>) {
+        match node {
+            Node::EnumDefinition(ed) => self.define_enum(ed),
+            Node::Match(mt) => {
+                check_match(mt, warnings);
+                self.check_exhaustive(mt, warnings);
+            }
+            _ => {}
+        }
+
+        for child in children(node) {
+            self.check_node(child, warnings);
+        }
+    }
+
+    fn define_enum(&mut self, ed: &EnumDefinition) {
+        let name = ed.name.token.clone();
+
+        let variants = ed
+            .variants
+            .data
+            .iter()
+            .map(|variant| (variant.name.token.clone(), variant.fields.data.len()))
+            .collect::<Vec<_>>();
+
+        for (variant, _) in variants.iter() {
+            self.variants.insert(variant.clone(), name.clone());
+        }
+
+        self.enums.insert(name, variants);
+    }
+
+    /// Warns when a match on an enum has no clause for some of its variants. The
+    /// enum is the one the first variant pattern names, and only clauses without
+    /// a guard and with irrefutable fields count as covering a variant.
+    fn check_exhaustive(&self, mt: &Match, warnings: &mut Vec<ParseWarning>) {
+        let unguarded = mt
+            .clauses
+            .data
+            .iter()
+            .filter(|clause| clause.guard.is_none())
+            .map(|clause| &clause.pattern)
+            .collect::<Vec<_>>();
+
+        if unguarded.iter().any(|pattern| irrefutable(pattern)) {
+            return;
+        }
+
+        let enumeration = mt
+            .clauses
+            .data
+            .iter()
+            .find_map(|clause| match &clause.pattern {
+                Pattern::Constructor { name, .. } => self.variants.get(&name.token),
+                _ => None,
+            });
+
+        let Some((enumeration, variants)) =
+            enumeration.and_then(|name| Some((name, self.enums.get(name)?)))
+        else {
+            return;
+        };
+
+        let missing = variants
+            .iter()
+            .filter(|(variant, count)| {
+                !unguarded.iter().any(|pattern| match pattern {
+                    Pattern::Constructor { name, fields, rest } => {
+                        name.token == *variant
+                            && rest.as_deref().is_none_or(irrefutable)
+                            && match rest {
+                                Some(_) => fields.data.len() <= *count,
+                                None => fields.data.len() == *count,
+                            }
+                            && fields.data.iter().all(irrefutable)
+                    }
+                    _ => false,
+                })
+            })
+            .map(|(variant, _)| variant.clone())
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            warnings.push(ParseWarning::NonExhaustiveMatch {
+                token: mt.token.clone(),
+                enumeration: enumeration.clone(),
+                missing: missing.join(", "),
+            });
+        }
+    }
+}
+
+/// The nodes directly nested inside `node`.
+fn children(node: &Node) -> Vec<&Node> {
+    match node {
+        Node::FunctionDefinition(fd) => arity_children(&fd.arities),
+        Node::Lambda(lambda) => arity_children(&lambda.arities),
+        Node::MultiDefinition(md) => vec![md.dispatch.as_ref()],
+        Node::MethodDefinition(md) => std::iter::once(md.value.as_ref())
+            .chain(arity_children(&md.arities))
+            .collect(),
+        Node::Conditional(conditional) => std::iter::once(conditional.condition.as_ref())
+            .chain(conditional.true_expression.data.iter())
+            .chain(conditional.false_expression.data.iter())
+            .collect(),
+        Node::Match(mt) => std::iter::once(mt.value.as_ref())
+            .chain(mt.clauses.data.iter().flat_map(|clause| {
+                clause
+                    .guard
+                    .iter()
+                    .map(|guard| guard.as_ref())
+                    .chain(clause.body.data.iter())
+            }))
+            .collect(),
+        Node::Try(tr) => tr
+            .body
+            .data
+            .iter()
+            .chain(tr.catch.iter().flat_map(|catch| catch.body.data.iter()))
+            .chain(tr.finally.data.iter())
+            .collect(),
+        Node::HandlerBind(hb) => hb.handlers.data.iter().chain(hb.body.data.iter()).collect(),
+        Node::RestartCase(rc) => std::iter::once(rc.body.as_ref())
+            .chain(
+                rc.restarts
+                    .data
+                    .iter()
+                    .flat_map(|c| c.arity.body.data.iter()),
+            )
+            .collect(),
+        Node::Logical(logical) => logical.operands.data.iter().collect(),
+        Node::While(wl) => std::iter::once(wl.condition.as_ref())
+            .chain(wl.body.data.iter())
+            .collect(),
+        Node::Loop(lp) => lp.values.data.iter().chain(lp.body.data.iter()).collect(),
+        Node::Recur(recur) => recur.arguments.data.iter().collect(),
+        Node::DoTimes(iteration) | Node::DoSeq(iteration) => {
+            std::iter::once(iteration.source.as_ref())
+                .chain(iteration.body.data.iter())
+                .collect()
+        }
+        Node::VariableDefinition(vd) => vec![vd.assignment.as_ref()],
+        Node::FunctionCall(fc) => fc.arguments.data.iter().collect(),
+        Node::List(list) => list.data.data.iter().collect(),
+        Node::Map(map) => map.data.data.iter().collect(),
+        Node::Set(set) => set.data.data.iter().collect(),
+        Node::Literal(_)
+        | Node::VariableCall(_)
+        | Node::StructDefinition(_)
+        | Node::EnumDefinition(_)
+        | Node::Namespace(_)
+        | Node::Require(_)
+        | Node::Documentation(_) => Vec::new(),
+    }
+}
+
+/// Default values and bodies of every arity.
+fn arity_children(arities: &Stack<Arity>) -> Vec<&Node> {
+    arities
+        .data
+        .iter()
+        .flat_map(|arity| {
+            let parameters = &arity.parameters;
+
+            parameters
+                .optional
+                .data
+                .iter()
+                .chain(parameters.keywords.data.iter())
+                .filter_map(|(_, default)| default.as_ref())
+                .chain(arity.body.data.iter())
+        })
+        .collect()
+}
+
+/// Warns about clauses whose pattern is covered by an earlier clause without a guard.
+fn check_match(mt: &Match, warnings: &mut Vec<ParseWarning>) {
+    for (index, clause) in mt.clauses.data.iter().enumerate() {
+        let shadowed =
+            mt.clauses.data.iter().take(index).any(|earlier| {
+                earlier.guard.is_none() && subsumes(&earlier.pattern, &clause.pattern)
+            });
+
+        if shadowed {
+            warnings.push(ParseWarning::UnreachableClause(
+                pattern_token(&clause.pattern).unwrap_or(&mt.token).clone(),
+            ));
+        }
+    }
+}
+
+fn irrefutable(pattern: &Pattern) -> bool {
+    matches!(pattern, Pattern::Wildcard(_) | Pattern::Bind(_))
+}
+
+/// Whether every value matched by `later` is already matched by `earlier`.
+fn subsumes(earlier: &Pattern, later: &Pattern) -> bool {
+    match (earlier, later) {
+        (earlier, _) if irrefutable(earlier) => true,
+        (Pattern::Literal(a), Pattern::Literal(b)) => {
+            a.token_kind == b.token_kind && a.token == b.token
+        }
+        (
+            Pattern::List {
+                items: earlier_items,
+                rest: earlier_rest,
+            },
+            Pattern::List {
+                items: later_items,
+                rest: later_rest,
+            },
+        ) => {
+            let (earlier_len, later_len) = (earlier_items.data.len(), later_items.data.len());
+
+            let lengths = match (earlier_rest, later_rest) {
+                (None, None) => earlier_len == later_len,
+                (None, Some(_)) => false,
+                (Some(rest), _) => irrefutable(rest) && earlier_len <= later_len,
+            };
+
+            lengths
+                && earlier_items
+                    .data
+                    .iter()
+                    .zip(later_items.data.iter())
+                    .all(|(a, b)| subsumes(a, b))
+        }
+        (
+            Pattern::Constructor {
+                name: earlier_name,
+                fields: earlier_fields,
+                rest: None,
+            },
+            Pattern::Constructor {
+                name: later_name,
+                fields: later_fields,
+                rest: None,
+            },
+        ) => {
+            earlier_name.token == later_name.token
+                && earlier_fields.data.len() == later_fields.data.len()
+                && earlier_fields
+                    .data
+                    .iter()
+                    .zip(later_fields.data.iter())
+                    .all(|(a, b)| subsumes(a, b))
+        }
+        (Pattern::Map { entries: earlier }, Pattern::Map { entries: later }) => {
+            earlier.data.iter().all(|(key, pattern)| {
+                later.data.iter().any(|(other_key, other_pattern)| {
+                    key.token == other_key.token && subsumes(pattern, other_pattern)
+                })
+            })
+        }
+        _ => false,
+    }
+}
+
+/// The first token inside a pattern, used to point warnings at the clause.
+fn pattern_token(pattern: &Pattern) -> Option<&Token> {
+    match pattern {
+        Pattern::Wildcard(token) | Pattern::Literal(token) | Pattern::Bind(token) => Some(token),
+        Pattern::List { items, rest } => items
+            .data
+            .iter()
+            .find_map(pattern_token)
+            .or_else(|| rest.as_deref().and_then(pattern_token)),
+        Pattern::Constructor { name, .. } => Some(name),
+        Pattern::Map { entries } => entries.data.front().map(|(key, _)| key),
+    }
+}
@@ -27,6 +27,26 @@ pub enum LexerError {
         #[label("Found another word, check the stack")]
         Token,
     ),
+
+    #[error("Parse Error: Unknown escape sequence {}", .0.token)]
+    #[diagnostic(help("Strings may escape \\\\, \\\", \\n, \\t and \\r"))]
+    EscapeError(
+        #[source_code]
+        #[label("Unknown escape")]
+        Token,
+    ),
+}
+
+impl LexerError {
+    /// The token the error points at.
+    pub fn token(&self) -> &Token {
+        match self {
+            LexerError::IncompleteStringError(token)
+            | LexerError::KeywordError(token)
+            | LexerError::ExtractWordError(token)
+            | LexerError::EscapeError(token) => token,
+        }
+    }
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -52,7 +72,154 @@ where
 
     ListDefinition(
         #[source_code]
-        #[label("LIST can only contain NUMBER, STRING, BOOLEAN OR another LIST")]
+        #[label("LIST must be closed with a RightSquareBracket")]
+        Token,
+    ),
+
+    MapDefinition(
+        #[source_code]
+        #[label("MAP must contain an even number of forms: key value pairs")]
+        Token,
+    ),
+
+    SetDefinition(
+        #[source_code]
+        #[label("SET must be closed with a RightCurlyBracket")]
+        Token,
+    ),
+
+    ConditionalDefinition(
+        #[source_code]
+        #[label("IF expects a condition, a true branch and an optional false branch")]
+        Token,
+    ),
+
+    CondDefinition(
+        #[source_code]
+        #[label("COND expects pairs of a test and an expression")]
+        Token,
+    ),
+
+    WhenDefinition(
+        #[source_code]
+        #[label("WHEN and UNLESS expect a condition followed by a body")]
+        Token,
+    ),
+
+    CaseDefinition(
+        #[source_code]
+        #[label("CASE values must be literals or lists of literals")]
+        Token,
+    ),
+
+    MatchDefinition(
+        #[source_code]
+        #[label("MATCH clauses must be lists of a pattern followed by a body")]
+        Token,
+    ),
+
+    PatternDefinition(
+        #[source_code]
+        #[label("Patterns are literals, symbols, or lists and maps of patterns")]
+        Token,
+    ),
+
+    DuplicateBinding(
+        #[source_code]
+        #[label("This name is already bound by the same pattern or parameter list")]
+        Token,
+    ),
+
+    TryDefinition(
+        #[source_code]
+        #[label("TRY expects a body followed by optional CATCH and FINALLY clauses")]
+        Token,
+    ),
+
+    HandlerDefinition(
+        #[source_code]
+        #[label("HANDLER-BIND expects pairs of a condition type and a handler")]
+        Token,
+    ),
+
+    RestartDefinition(
+        #[source_code]
+        #[label("RESTART-CASE clauses are a name, a parameter list and a body")]
+        Token,
+    ),
+
+    WhileDefinition(
+        #[source_code]
+        #[label("WHILE expects a condition followed by a body")]
+        Token,
+    ),
+
+    BindingDefinition(
+        #[source_code]
+        #[label("Bindings must be pairs of a SYMBOL and a value")]
+        Token,
+    ),
+
+    RecurPosition(
+        #[source_code]
+        #[label("RECUR must be in tail position of a LOOP")]
+        Token,
+    ),
+
+    #[error("recur expects {expected} arguments, found {found}")]
+    RecurArity {
+        #[source_code]
+        #[label("One value is needed for every LOOP binding")]
+        token: Token,
+        expected: usize,
+        found: usize,
+    },
+
+    StructDefinition(
+        #[source_code]
+        #[label("DEFSTRUCT expects a name followed by field names")]
+        Token,
+    ),
+
+    DuplicateField(
+        #[source_code]
+        #[label("Every field of a DEFSTRUCT needs a name of its own")]
+        Token,
+    ),
+
+    EnumDefinition(
+        #[source_code]
+        #[label("DEFENUM expects a name followed by (Variant field...) forms")]
+        Token,
+    ),
+
+    DuplicateVariant(
+        #[source_code]
+        #[label("Every variant of a DEFENUM needs a name of its own")]
+        Token,
+    ),
+
+    MultiDefinition(
+        #[source_code]
+        #[label("DEFMULTI expects a name followed by a dispatch function")]
+        Token,
+    ),
+
+    MethodDefinition(
+        #[source_code]
+        #[label("DEFMETHOD expects a name, a dispatch value and parameters")]
+        Token,
+    ),
+
+    NamespaceDefinition(
+        #[source_code]
+        #[label("NS expects a module name")]
+        Token,
+    ),
+
+    RequireDefinition(
+        #[source_code]
+        #[label("REQUIRE expects a module name, optionally followed by :as alias")]
         Token,
     ),
 
@@ -76,6 +243,13 @@ where
         Token,
     ),
 
+    #[error("function parameters")]
+    ParameterDefinition(
+        #[source_code]
+        #[label("Parameters are patterns followed by an &optional section, then a & rest or &key section")]
+        Token,
+    ),
+
     FunctionDocstring(
         #[source_code]
         #[label("Function Docstring must be a symbol")]
@@ -114,3 +288,69 @@ where
         Token,
     ),
 }
+
+impl ParseError<Token> {
+    /// The token the error points at, the last one left for stack errors.
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            ParseError::StackError { stack, .. } => stack.last(),
+            ParseError::RecurArity { token, .. } => Some(token),
+            ParseError::VariableDefinition(token)
+            | ParseError::VariableAssignment(token)
+            | ParseError::ListDefinition(token)
+            | ParseError::MapDefinition(token)
+            | ParseError::SetDefinition(token)
+            | ParseError::ConditionalDefinition(token)
+            | ParseError::CondDefinition(token)
+            | ParseError::WhenDefinition(token)
+            | ParseError::CaseDefinition(token)
+            | ParseError::MatchDefinition(token)
+            | ParseError::PatternDefinition(token)
+            | ParseError::DuplicateBinding(token)
+            | ParseError::TryDefinition(token)
+            | ParseError::HandlerDefinition(token)
+            | ParseError::RestartDefinition(token)
+            | ParseError::WhileDefinition(token)
+            | ParseError::BindingDefinition(token)
+            | ParseError::RecurPosition(token)
+            | ParseError::StructDefinition(token)
+            | ParseError::DuplicateField(token)
+            | ParseError::EnumDefinition(token)
+            | ParseError::DuplicateVariant(token)
+            | ParseError::MultiDefinition(token)
+            | ParseError::MethodDefinition(token)
+            | ParseError::NamespaceDefinition(token)
+            | ParseError::RequireDefinition(token)
+            | ParseError::FunctionName(token)
+            | ParseError::NoLeftBracket(token)
+            | ParseError::FunctionParameter(token)
+            | ParseError::ParameterDefinition(token)
+            | ParseError::FunctionDocstring(token)
+            | ParseError::FunctionBody(token)
+            | ParseError::VariableCall(token)
+            | ParseError::FunctionCallName(token)
+            | ParseError::FunctionCallArg(token)
+            | ParseError::ParseError(token) => Some(token),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error, Diagnostic)]
+#[diagnostic(severity(Warning))]
+pub enum ParseWarning {
+    #[error("unreachable match clause")]
+    UnreachableClause(
+        #[source_code]
+        #[label("An earlier clause already matches every value this one does")]
+        Token,
+    ),
+
+    #[error("match is not exhaustive, missing {missing}")]
+    NonExhaustiveMatch {
+        #[source_code]
+        #[label("No clause matches these variants of {enumeration}")]
+        token: Token,
+        enumeration: String,
+        missing: String,
+    },
+}
@@ -4,16 +4,21 @@ use crate::{
     stack::Stack,
     token::{Token, TokenKind},
 };
-use std::{iter::Peekable, path::PathBuf, str::Chars};
+use std::{iter::Peekable, path::Path, str::Chars};
 
-const SPECIAL_CHARS: [char; 5] = ['(', ')', '[', ']', '\"'];
+const SPECIAL_CHARS: [char; 7] = ['(', ')', '[', ']', '{', '}', '\"'];
 
 const KEYWORDS: [&str; 3] = ["defn", "setq", "if"];
 
 fn peek_for_keywords(chars: &mut Peekable<Chars>) -> Option<&'static str> {
     for &keyword in &KEYWORDS {
-        let next_chars: String = chars.clone().take(keyword.len() + 1).collect();
-        if &next_chars[1..] == keyword {
+        let next_chars: String = chars.clone().take(keyword.len() + 2).collect();
+        let boundary = next_chars
+            .chars()
+            .nth(keyword.len() + 1)
+            .is_none_or(|ch| ch.is_whitespace() || SPECIAL_CHARS.contains(&ch));
+
+        if next_chars[1..].starts_with(keyword) && boundary {
             for _ in 0..=keyword.len() {
                 chars.next();
             }
@@ -28,18 +33,43 @@ fn extract_string_content(
     stack: &Stack<Token>,
     row: usize,
     col: usize,
-    namespace: &PathBuf,
+    namespace: &Path,
 ) -> Result<Token, LexerError> {
     // check for docstrings
     let mut res = String::new();
 
     chars.next(); // skip the opening quote
 
-    for inner_ch in chars.by_ref() {
-        if inner_ch == '\"' {
-            break;
+    // characters read so far, the opening quote included
+    let mut read = 1;
+
+    while let Some(inner_ch) = chars.next() {
+        read += 1;
+
+        match inner_ch {
+            '\"' => break,
+            '\\' => match chars.next() {
+                Some('\\') => res.push('\\'),
+                Some('"') => res.push('"'),
+                Some('n') => res.push('\n'),
+                Some('t') => res.push('\t'),
+                Some('r') => res.push('\r'),
+                Some(escaped) => {
+                    let sequence = format!("\\{}", escaped);
+
+                    return Err(LexerError::EscapeError(Token::new(
+                        &sequence,
+                        TokenKind::Error,
+                        row,
+                        col + read - 1,
+                        sequence.len(),
+                        namespace,
+                    )));
+                }
+                None => break,
+            },
+            _ => res.push(inner_ch),
         }
-        res.push(inner_ch);
     }
 
     if let Some(token) = stack.last() {
@@ -66,7 +96,7 @@ fn extract_string_content(
             res.as_str(),
             TokenKind::Error,
             row,
-            col.clone(),
+            col,
             0,
             namespace,
         )))
@@ -77,7 +107,7 @@ fn extract_word(
     chars: &mut Peekable<Chars>,
     row: usize,
     col: usize,
-    namespace: &PathBuf,
+    namespace: &Path,
 ) -> Result<String, LexerError> {
     let mut word = String::new();
 
@@ -103,7 +133,7 @@ fn extract_word(
     Ok(word)
 }
 
-pub fn tokenise(code: &String, namespace: &PathBuf) -> Result<Stack<Token>, LexerError> {
+pub fn tokenise(code: &str, namespace: &Path) -> Result<Stack<Token>, LexerError> {
     let mut stack = Stack::new();
     let mut chars = code.chars().peekable();
     // keep track of right and left brace pairs
@@ -122,6 +152,8 @@ pub fn tokenise(code: &String, namespace: &PathBuf) -> Result<Stack<Token>, Lexe
                 chars.next();
             }
             '(' => {
+                counter += 1;
+
                 if let Some(keyword) = peek_for_keywords(&mut chars) {
                     match keyword {
                         "defn" => push_token!(
@@ -167,7 +199,6 @@ pub fn tokenise(code: &String, namespace: &PathBuf) -> Result<Stack<Token>, Lexe
 
                     col += keyword.len();
                 } else {
-                    counter += 1;
                     push_token!(
                         chars,
                         stack,
@@ -218,9 +249,44 @@ pub fn tokenise(code: &String, namespace: &PathBuf) -> Result<Stack<Token>, Lexe
                     namespace
                 );
             }
+            '{' => {
+                push_token!(
+                    chars,
+                    stack,
+                    &ch,
+                    TokenKind::LeftCurlyBracket,
+                    row,
+                    col,
+                    namespace
+                );
+            }
+            '}' => {
+                push_token!(
+                    chars,
+                    stack,
+                    &ch,
+                    TokenKind::RightCurlyBracket,
+                    row,
+                    col,
+                    namespace
+                );
+            }
+            '#' if chars.clone().nth(1) == Some('{') => {
+                chars.next();
+                push_token!(
+                    chars,
+                    stack,
+                    "#{",
+                    TokenKind::LeftSetBracket,
+                    row,
+                    col,
+                    namespace
+                );
+                col += 1;
+            }
             '\"' => {
                 let string_content =
-                    extract_string_content(&mut chars, &stack, row, col, &namespace)?;
+                    extract_string_content(&mut chars, &stack, row, col, namespace)?;
                 stack.push(string_content);
             }
             ch if ch.is_whitespace() => {
@@ -229,7 +295,7 @@ pub fn tokenise(code: &String, namespace: &PathBuf) -> Result<Stack<Token>, Lexe
             ';' => {
                 if chars.clone().take(2).collect::<String>() == ";;" {
                     // Skip the entire line
-                    while let Some(next_char) = chars.next() {
+                    for next_char in chars.by_ref() {
                         if next_char == '\n' {
                             row += 1;
                             col = 0;
@@ -242,26 +308,30 @@ pub fn tokenise(code: &String, namespace: &PathBuf) -> Result<Stack<Token>, Lexe
                 }
             }
             _ => {
-                let word = extract_word(&mut chars, row, col, &namespace)?;
-                if let Ok(_) = word.parse::<i64>() {
-                    stack.push(Token::new(
-                        word.as_str(),
-                        TokenKind::Integer,
-                        row,
-                        col,
-                        word.len(),
-                        namespace,
-                    ));
+                let word = extract_word(&mut chars, row, col, namespace)?;
+
+                let token_kind = if word.parse::<i64>().is_ok() {
+                    TokenKind::Integer
+                } else if word.contains('.') && word.parse::<f64>().is_ok() {
+                    TokenKind::Float
+                } else if word == "true" || word == "false" {
+                    TokenKind::Bool
+                } else if word == "nil" {
+                    TokenKind::Nil
+                } else if word.len() > 1 && word.starts_with(':') {
+                    TokenKind::Keyword
                 } else {
-                    stack.push(Token::new(
-                        word.as_str(),
-                        TokenKind::Symbol,
-                        row,
-                        col,
-                        word.len(),
-                        namespace,
-                    ));
-                }
+                    TokenKind::Symbol
+                };
+
+                stack.push(Token::new(
+                    word.as_str(),
+                    token_kind,
+                    row,
+                    col,
+                    word.len(),
+                    namespace,
+                ));
                 col += word.len() - 1;
             }
         }
@@ -5,6 +5,26 @@ use crate::{
     token::{Token, TokenKind},
 };
 
+fn expect_closing(
+    tokens: &mut Stack<Token>,
+    name: &'static str,
+    kind: TokenKind,
+) -> Result<Token, ParseError<Token>> {
+    tokens
+        .pop_front()
+        .ok_or(ParseError::StackError {
+            name,
+            stack: tokens.clone(),
+        })
+        .and_then(|token| {
+            if token.token_kind == kind {
+                Ok(token)
+            } else {
+                Err(ParseError::ParseError(token))
+            }
+        })
+}
+
 fn parse_variable_definition(tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
     let var_token = tokens.pop_front().ok_or(ParseError::StackError {
         name: "variable name",
@@ -22,51 +42,233 @@ fn parse_variable_definition(tokens: &mut Stack<Token>) -> Result<Node, ParseErr
     })?;
 
     let value = match value_token.token_kind {
-        TokenKind::Integer => value_token,
-        TokenKind::Bool => value_token,
-        TokenKind::StringLiteral => value_token,
-        _ => return Err(ParseError::VariableAssignment(value_token)),
+        TokenKind::RightRoundBracket => return Err(ParseError::VariableAssignment(value_token)),
+        _ => parse_node(value_token, tokens)?,
     };
 
+    expect_closing(
+        tokens,
+        "No closing bracket for variable definition",
+        TokenKind::RightRoundBracket,
+    )?;
+
     let var = VariableDefinition {
         name: var_name,
-        assignment: value,
+        assignment: Box::new(value),
     };
 
     Ok(Node::VariableDefinition(var))
 }
 
-fn parse_list(tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+fn parse_list_items(tokens: &mut Stack<Token>) -> Result<Stack<Node>, ParseError<Token>> {
     let mut list_args: Stack<Node> = Stack::new();
 
-    let mut nested_level = 0;
+    loop {
+        let list_arg = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for list",
+            stack: tokens.clone(),
+        })?;
 
-    while let Some(list_arg) = tokens.pop_front() {
         match list_arg.token_kind {
-            TokenKind::Bool | TokenKind::Integer | TokenKind::StringLiteral => {
-                list_args.push(Node::Literal(list_arg))
-            }
-            TokenKind::LeftSquareBracket => {
-                nested_level += 1;
-                let res = parse_list(tokens)?;
-                list_args.push(res)
-            }
-            TokenKind::RightSquareBracket => {
-                nested_level -= 1;
-                if nested_level == 0 {
-                    break;
+            TokenKind::RightSquareBracket => break,
+            TokenKind::RightRoundBracket => return Err(ParseError::ListDefinition(list_arg)),
+            _ => list_args.push(parse_node(list_arg, tokens)?),
+        }
+    }
+
+    Ok(list_args)
+}
+
+fn parse_list(tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let node = List {
+        data: parse_list_items(tokens)?,
+    };
+
+    Ok(Node::List(node))
+}
+
+fn parse_map(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut entries: Stack<Node> = Stack::new();
+
+    loop {
+        let entry = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for map",
+            stack: tokens.clone(),
+        })?;
+
+        match entry.token_kind {
+            TokenKind::RightCurlyBracket => break,
+            TokenKind::RightRoundBracket | TokenKind::RightSquareBracket => {
+                return Err(ParseError::MapDefinition(entry))
+            }
+            _ => entries.push(parse_node(entry, tokens)?),
+        }
+    }
+
+    if !entries.data.len().is_multiple_of(2) {
+        return Err(ParseError::MapDefinition(token));
+    }
+
+    Ok(Node::Map(Map { data: entries }))
+}
+
+fn parse_set(tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut members: Stack<Node> = Stack::new();
+
+    loop {
+        let member = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for set",
+            stack: tokens.clone(),
+        })?;
+
+        match member.token_kind {
+            TokenKind::RightCurlyBracket => break,
+            TokenKind::RightRoundBracket | TokenKind::RightSquareBracket => {
+                return Err(ParseError::SetDefinition(member))
+            }
+            _ => members.push(parse_node(member, tokens)?),
+        }
+    }
+
+    Ok(Node::Set(Set { data: members }))
+}
+
+#[derive(PartialEq)]
+enum Section {
+    Required,
+    Optional,
+    Rest,
+    Keywords,
+}
+
+/// An optional or keyword parameter: a symbol, or `[name default]`.
+fn parse_default(bracket: &Token, node: Node) -> Result<(Token, Option<Node>), ParseError<Token>> {
+    match node {
+        Node::VariableCall(vc) => Ok((vc.name, None)),
+        Node::List(mut list) if list.data.data.len() == 2 => {
+            match (list.data.pop_front(), list.data.pop_front()) {
+                (Some(Node::VariableCall(vc)), Some(default)) => Ok((vc.name, Some(default))),
+                _ => Err(ParseError::FunctionParameter(bracket.clone())),
+            }
+        }
+        _ => Err(ParseError::FunctionParameter(bracket.clone())),
+    }
+}
+
+fn parse_parameters(tokens: &mut Stack<Token>) -> Result<Parameters, ParseError<Token>> {
+    let left_bracket = tokens
+        .pop_front()
+        .ok_or(ParseError::StackError {
+            name: "No left braket in stack",
+            stack: tokens.clone(),
+        })
+        .and_then(|lft| match lft.token_kind {
+            TokenKind::LeftSquareBracket => Ok(lft),
+            _ => Err(ParseError::NoLeftBracket(lft)),
+        })?;
+
+    let mut parameters = Parameters::default();
+    let mut section = Section::Required;
+    let mut bound = Vec::new();
+
+    for node in parse_list_items(tokens)?.data {
+        let marker = match &node {
+            Node::VariableCall(vc) => match vc.name.token.as_str() {
+                "&optional" => Some((Section::Optional, vc.name.clone())),
+                "&" => Some((Section::Rest, vc.name.clone())),
+                "&key" => Some((Section::Keywords, vc.name.clone())),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some((next, marker)) = marker {
+            let allowed = match next {
+                Section::Rest | Section::Keywords => {
+                    matches!(section, Section::Required | Section::Optional)
                 }
+                _ => section == Section::Required,
+            };
+
+            if !allowed {
+                return Err(ParseError::ParameterDefinition(marker));
+            }
+
+            section = next;
+            continue;
+        }
+
+        match section {
+            Section::Required => {
+                let pattern = parse_pattern(&left_bracket, node)?;
+                bind_once(&pattern, &mut bound)?;
+                parameters.required.push(pattern);
+            }
+            Section::Optional => {
+                let (name, default) = parse_default(&left_bracket, node)?;
+                bind_once(&Pattern::Bind(name.clone()), &mut bound)?;
+                parameters.optional.push((name, default));
+            }
+            Section::Rest if parameters.rest.is_none() => {
+                let pattern = parse_pattern(&left_bracket, node)?;
+                bind_once(&pattern, &mut bound)?;
+                parameters.rest = Some(pattern);
+            }
+            Section::Rest => return Err(ParseError::ParameterDefinition(left_bracket)),
+            Section::Keywords => {
+                let (name, default) = parse_default(&left_bracket, node)?;
+                bind_once(&Pattern::Bind(name.clone()), &mut bound)?;
+                parameters.keywords.push((name, default));
             }
-            _ => return Err(ParseError::ListDefinition(list_arg)),
         }
     }
 
-    let node = List { data: list_args };
+    if section == Section::Rest && parameters.rest.is_none() {
+        return Err(ParseError::ParameterDefinition(left_bracket));
+    }
 
-    Ok(Node::List(node))
+    Ok(parameters)
+}
+
+/// Reads `([params] body...) ...` up to the closing bracket of the definition,
+/// one arity per clause.
+fn parse_arities(
+    token: &Token,
+    tokens: &mut Stack<Token>,
+) -> Result<Stack<Arity>, ParseError<Token>> {
+    let mut arities: Stack<Arity> = Stack::new();
+
+    loop {
+        let clause = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for function arities",
+            stack: tokens.clone(),
+        })?;
+
+        match clause.token_kind {
+            TokenKind::RightRoundBracket => break,
+            TokenKind::LeftRoundBracket => {
+                let parameters = parse_parameters(tokens)?;
+                let body = parse_body(tokens, "Function body stack is empty")?;
+
+                arities.push(Arity { parameters, body });
+            }
+            _ => return Err(ParseError::FunctionBody(clause)),
+        }
+    }
+
+    if arities.is_empty() {
+        return Err(ParseError::FunctionBody(token.clone()));
+    }
+
+    Ok(arities)
 }
 
-fn parse_function_definition(tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+/// `(defn name [params] "doc" body...)`, or with several arities
+/// `(defn name "doc" ([params] body...) ...)`. `defn-` takes the same forms.
+fn parse_function_definition(
+    tokens: &mut Stack<Token>,
+    private: bool,
+) -> Result<Node, ParseError<Token>> {
     let name = tokens
         .pop_front()
         .ok_or(ParseError::StackError {
@@ -75,30 +277,29 @@ fn parse_function_definition(tokens: &mut Stack<Token>) -> Result<Node, ParseErr
         })
         .and_then(|name| match name.token_kind {
             TokenKind::Symbol => Ok(name),
-            _ => return Err(ParseError::FunctionName(name)),
+            _ => Err(ParseError::FunctionName(name)),
         })?;
 
-    let _left_bracket = tokens
-        .pop_front()
-        .ok_or(ParseError::StackError {
-            name: "No left braket in stack",
+    if let Some(TokenKind::StringLiteral) = tokens.first().map(|token| &token.token_kind) {
+        let docstrings = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No DocString Token in Stack",
             stack: tokens.clone(),
-        })
-        .and_then(|lft| match lft.token_kind {
-            TokenKind::LeftSquareBracket => Ok(lft),
-            _ => return Err(ParseError::NoLeftBracket(lft)),
         })?;
 
-    let mut parameters: Stack<Token> = Stack::new();
+        let arities = parse_arities(&name, tokens)?;
 
-    while let Some(function_arg) = tokens.pop_front() {
-        match function_arg.token_kind {
-            TokenKind::Symbol => parameters.push(function_arg),
-            TokenKind::RightSquareBracket => break,
-            _ => return Err(ParseError::FunctionParameter(function_arg)),
-        }
+        let fd = FunctionDefinition {
+            name,
+            docstrings,
+            arities,
+            private,
+        };
+
+        return Ok(Node::FunctionDefinition(fd));
     }
 
+    let parameters = parse_parameters(tokens)?;
+
     let docstrings = tokens
         .pop_front()
         .ok_or(ParseError::StackError {
@@ -110,31 +311,953 @@ fn parse_function_definition(tokens: &mut Stack<Token>) -> Result<Node, ParseErr
             _ => Err(ParseError::FunctionDocstring(doc_string)),
         })?;
 
-    if let Some(first_token) = tokens.pop_front() {
-        if first_token.token_kind != TokenKind::LeftRoundBracket {
-            return Err(ParseError::FunctionBody(first_token));
+    let body = parse_body(tokens, "Function body stack is empty")?;
+
+    if body.is_empty() {
+        return Err(ParseError::StackError {
+            name: "Function body stack is empty",
+            stack: tokens.clone(),
+        });
+    }
+
+    let mut arities: Stack<Arity> = Stack::new();
+    arities.push(Arity { parameters, body });
+
+    let fd = FunctionDefinition {
+        name,
+        docstrings,
+        arities,
+        private,
+    };
+
+    Ok(Node::FunctionDefinition(fd))
+}
+
+fn parse_struct_definition(
+    token: Token,
+    tokens: &mut Stack<Token>,
+) -> Result<StructDefinition, ParseError<Token>> {
+    let name = match tokens.pop_front() {
+        Some(name) if name.token_kind == TokenKind::Symbol => name,
+        Some(other) => return Err(ParseError::StructDefinition(other)),
+        None => return Err(ParseError::StructDefinition(token)),
+    };
+
+    if let Some(TokenKind::StringLiteral) = tokens.first().map(|token| &token.token_kind) {
+        tokens.pop_front();
+    }
+
+    let mut fields: Stack<Token> = Stack::new();
+
+    loop {
+        let field = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for defstruct",
+            stack: tokens.clone(),
+        })?;
+
+        match field.token_kind {
+            TokenKind::RightRoundBracket => break,
+            TokenKind::Symbol if fields.data.iter().any(|seen| seen.token == field.token) => {
+                return Err(ParseError::DuplicateField(field))
+            }
+            TokenKind::Symbol => fields.push(field),
+            _ => return Err(ParseError::StructDefinition(field)),
         }
+    }
 
-        // Process the first LeftRoundBracket
-        let mut body: Stack<Node> = Stack::new();
+    Ok(StructDefinition { name, fields })
+}
 
-        let res = parse_expression(tokens)?;
+fn parse_enum_definition(
+    token: Token,
+    tokens: &mut Stack<Token>,
+) -> Result<Node, ParseError<Token>> {
+    let name = match tokens.pop_front() {
+        Some(name) if name.token_kind == TokenKind::Symbol => name,
+        Some(other) => return Err(ParseError::EnumDefinition(other)),
+        None => return Err(ParseError::EnumDefinition(token)),
+    };
 
-        body.push(res);
+    if let Some(TokenKind::StringLiteral) = tokens.first().map(|token| &token.token_kind) {
+        tokens.pop_front();
+    }
 
-        let fd = FunctionDefinition {
+    let mut variants: Stack<StructDefinition> = Stack::new();
+
+    loop {
+        let variant = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for defenum",
+            stack: tokens.clone(),
+        })?;
+
+        match variant.token_kind {
+            TokenKind::RightRoundBracket => break,
+            TokenKind::LeftRoundBracket => {
+                let variant = parse_struct_definition(variant, tokens)?;
+
+                if variants
+                    .data
+                    .iter()
+                    .any(|seen| seen.name.token == variant.name.token)
+                {
+                    return Err(ParseError::DuplicateVariant(variant.name));
+                }
+
+                variants.push(variant);
+            }
+            _ => return Err(ParseError::EnumDefinition(variant)),
+        }
+    }
+
+    if variants.is_empty() {
+        return Err(ParseError::EnumDefinition(name));
+    }
+
+    Ok(Node::EnumDefinition(EnumDefinition { name, variants }))
+}
+
+fn parse_namespace(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let name = match tokens.pop_front() {
+        Some(name) if name.token_kind == TokenKind::Symbol => name,
+        Some(other) => return Err(ParseError::NamespaceDefinition(other)),
+        None => return Err(ParseError::NamespaceDefinition(token)),
+    };
+
+    if let Some(TokenKind::StringLiteral) = tokens.first().map(|token| &token.token_kind) {
+        tokens.pop_front();
+    }
+
+    expect_closing(
+        tokens,
+        "No closing bracket for ns",
+        TokenKind::RightRoundBracket,
+    )?;
+
+    Ok(Node::Namespace(Namespace { token, name }))
+}
+
+fn parse_require(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let module = match tokens.pop_front() {
+        Some(module) if module.token_kind == TokenKind::Symbol => module,
+        Some(other) => return Err(ParseError::RequireDefinition(other)),
+        None => return Err(ParseError::RequireDefinition(token)),
+    };
+
+    let alias = match tokens.first() {
+        Some(option) if option.token_kind == TokenKind::Keyword && option.token == ":as" => {
+            tokens.pop_front();
+
+            match tokens.pop_front() {
+                Some(alias) if alias.token_kind == TokenKind::Symbol => Some(alias),
+                Some(other) => return Err(ParseError::RequireDefinition(other)),
+                None => return Err(ParseError::RequireDefinition(module)),
+            }
+        }
+        _ => None,
+    };
+
+    expect_closing(
+        tokens,
+        "No closing bracket for require",
+        TokenKind::RightRoundBracket,
+    )?;
+
+    Ok(Node::Require(Require {
+        token,
+        module,
+        alias,
+    }))
+}
+
+/// Reads nodes up to and including the closing round bracket of the enclosing form.
+fn parse_body(
+    tokens: &mut Stack<Token>,
+    name: &'static str,
+) -> Result<Stack<Node>, ParseError<Token>> {
+    let mut body: Stack<Node> = Stack::new();
+
+    loop {
+        let token = tokens.pop_front().ok_or(ParseError::StackError {
             name,
-            parameters,
-            docstrings,
-            body,
+            stack: tokens.clone(),
+        })?;
+
+        match token.token_kind {
+            TokenKind::RightRoundBracket => break,
+            _ => body.push(parse_node(token, tokens)?),
+        }
+    }
+
+    Ok(body)
+}
+
+/// `[params] body...)` or `([params] body...) ...)`, the arities of a `fn`.
+fn parse_lambda_arities(
+    token: &Token,
+    tokens: &mut Stack<Token>,
+) -> Result<Stack<Arity>, ParseError<Token>> {
+    match tokens.first().map(|token| &token.token_kind) {
+        Some(TokenKind::LeftRoundBracket) => parse_arities(token, tokens),
+        _ => {
+            let parameters = parse_parameters(tokens)?;
+            let body = parse_body(tokens, "Lambda body stack is empty")?;
+
+            let mut arities: Stack<Arity> = Stack::new();
+            arities.push(Arity { parameters, body });
+            Ok(arities)
+        }
+    }
+}
+
+/// `(fn [params] body...)` or `(fn ([params] body...) ...)`
+fn parse_lambda(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let arities = parse_lambda_arities(&token, tokens)?;
+
+    Ok(Node::Lambda(Lambda { token, arities }))
+}
+
+fn parse_multi_definition(
+    token: Token,
+    tokens: &mut Stack<Token>,
+) -> Result<Node, ParseError<Token>> {
+    let name = match tokens.pop_front() {
+        Some(name) if name.token_kind == TokenKind::Symbol => name,
+        Some(other) => return Err(ParseError::MultiDefinition(other)),
+        None => return Err(ParseError::MultiDefinition(token)),
+    };
+
+    if let Some(TokenKind::StringLiteral) = tokens.first().map(|token| &token.token_kind) {
+        tokens.pop_front();
+    }
+
+    let mut forms = parse_body(tokens, "No closing bracket for defmulti")?;
+
+    let dispatch = match (forms.pop_front(), forms.is_empty()) {
+        (Some(dispatch), true) => dispatch,
+        _ => return Err(ParseError::MultiDefinition(name)),
+    };
+
+    Ok(Node::MultiDefinition(MultiDefinition {
+        name,
+        dispatch: Box::new(dispatch),
+    }))
+}
+
+fn parse_method_definition(
+    token: Token,
+    tokens: &mut Stack<Token>,
+) -> Result<Node, ParseError<Token>> {
+    let name = match tokens.pop_front() {
+        Some(name) if name.token_kind == TokenKind::Symbol => name,
+        Some(other) => return Err(ParseError::MethodDefinition(other)),
+        None => return Err(ParseError::MethodDefinition(token)),
+    };
+
+    let value = match tokens.pop_front() {
+        Some(value) if value.token_kind != TokenKind::RightRoundBracket => {
+            parse_node(value, tokens)?
+        }
+        _ => return Err(ParseError::MethodDefinition(name)),
+    };
+
+    let arities = parse_lambda_arities(&name, tokens)?;
+
+    Ok(Node::MethodDefinition(MethodDefinition {
+        name,
+        value: Box::new(value),
+        arities,
+    }))
+}
+
+fn parse_conditional(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut branches = parse_body(tokens, "No closing bracket for conditional")?;
+
+    if branches.data.len() < 2 || branches.data.len() > 3 {
+        return Err(ParseError::ConditionalDefinition(token));
+    }
+
+    let condition = branches
+        .pop_front()
+        .ok_or(ParseError::ConditionalDefinition(token))?;
+
+    let mut true_expression: Stack<Node> = Stack::new();
+    let mut false_expression: Stack<Node> = Stack::new();
+
+    if let Some(node) = branches.pop_front() {
+        true_expression.push(node);
+    }
+
+    if let Some(node) = branches.pop_front() {
+        false_expression.push(node);
+    }
+
+    let conditional = Conditional {
+        condition: Box::new(condition),
+        true_expression,
+        false_expression,
+    };
+
+    Ok(Node::Conditional(conditional))
+}
+
+/// Lowers `(cond test expression ...)` to nested conditionals. A final `else`
+/// test always holds, and no matching test evaluates to nil.
+fn parse_cond(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut clauses = parse_body(tokens, "No closing bracket for cond")?;
+
+    if !clauses.data.len().is_multiple_of(2) {
+        return Err(ParseError::CondDefinition(token));
+    }
+
+    let mut lowered: Stack<Node> = Stack::new();
+
+    while let (Some(expression), Some(test)) = (clauses.pop(), clauses.pop()) {
+        let condition = match test {
+            Node::VariableCall(vc) if vc.name.token == "else" => Node::Literal(Token {
+                token: "true".to_string(),
+                token_kind: TokenKind::Bool,
+                ..vc.name
+            }),
+            test => test,
         };
-        return Ok(Node::FunctionDefinition(fd));
-    } else {
-        return Err(ParseError::StackError {
-            name: "Function body stack is empty",
+
+        let mut true_expression: Stack<Node> = Stack::new();
+        true_expression.push(expression);
+
+        let conditional = Conditional {
+            condition: Box::new(condition),
+            true_expression,
+            false_expression: lowered,
+        };
+
+        lowered = Stack::new();
+        lowered.push(Node::Conditional(conditional));
+    }
+
+    Ok(lowered.pop().unwrap_or(Node::Literal(Token {
+        token: "nil".to_string(),
+        token_kind: TokenKind::Nil,
+        ..token
+    })))
+}
+
+/// Lowers `when` and `unless` to a conditional with the body on one branch.
+fn parse_when(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut body = parse_body(tokens, "No closing bracket for when")?;
+
+    let condition = body
+        .pop_front()
+        .ok_or(ParseError::WhenDefinition(token.clone()))?;
+
+    let (true_expression, false_expression) = match token.token.as_str() {
+        "unless" => (Stack::new(), body),
+        _ => (body, Stack::new()),
+    };
+
+    let conditional = Conditional {
+        condition: Box::new(condition),
+        true_expression,
+        false_expression,
+    };
+
+    Ok(Node::Conditional(conditional))
+}
+
+/// A case value is a single literal, or a list of literals that all select the clause.
+fn case_values(token: &Token, node: Node) -> Result<Stack<Token>, ParseError<Token>> {
+    let mut values: Stack<Token> = Stack::new();
+
+    let nodes = match node {
+        Node::List(list) => list.data,
+        node => {
+            let mut single = Stack::new();
+            single.push(node);
+            single
+        }
+    };
+
+    for node in nodes.data {
+        match node {
+            Node::Literal(token) => values.push(token),
+            Node::VariableCall(vc) => return Err(ParseError::CaseDefinition(vc.name)),
+            _ => return Err(ParseError::CaseDefinition(token.clone())),
+        }
+    }
+
+    Ok(values)
+}
+
+/// Lowers `(case key value expression ... default)` to nested conditionals, the
+/// default being the last form when the clauses are not paired up. The key is
+/// bound once by a `match` under a name no symbol can spell, and each clause
+/// tests it against its values with [`CASE_EQUAL`].
+fn parse_case(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut forms = parse_body(tokens, "No closing bracket for case")?;
+
+    let key = forms
+        .pop_front()
+        .ok_or(ParseError::CaseDefinition(token.clone()))?;
+
+    let mut lowered: Stack<Node> = Stack::new();
+
+    if !forms.data.len().is_multiple_of(2) {
+        if let Some(node) = forms.pop() {
+            lowered.push(node);
+        }
+    }
+
+    let symbol = |name: &str| Token {
+        token: name.to_string(),
+        token_kind: TokenKind::Symbol,
+        ..token.clone()
+    };
+
+    let bound = symbol("case key");
+
+    while let (Some(expression), Some(values)) = (forms.pop(), forms.pop()) {
+        let mut tests: Stack<Node> = Stack::new();
+
+        for value in case_values(&token, values)?.data {
+            let mut arguments: Stack<Node> = Stack::new();
+            arguments.push(Node::VariableCall(VariableCall {
+                name: bound.clone(),
+            }));
+            arguments.push(Node::Literal(value));
+
+            tests.push(Node::FunctionCall(FunctionCall {
+                name: symbol(CASE_EQUAL),
+                arguments,
+            }));
+        }
+
+        let mut true_expression: Stack<Node> = Stack::new();
+        true_expression.push(expression);
+
+        let conditional = Conditional {
+            condition: Box::new(Node::Logical(Logical {
+                operator: symbol("or"),
+                operands: tests,
+            })),
+            true_expression,
+            false_expression: lowered,
+        };
+
+        lowered = Stack::new();
+        lowered.push(Node::Conditional(conditional));
+    }
+
+    let mut clauses: Stack<MatchClause> = Stack::new();
+    clauses.push(MatchClause {
+        pattern: Pattern::Bind(bound),
+        guard: None,
+        body: lowered,
+    });
+
+    Ok(Node::Match(Match {
+        token,
+        value: Box::new(key),
+        clauses,
+    }))
+}
+
+/// Turns a parsed form into a pattern: `_` is a wildcard, other symbols bind, and
+/// lists and maps hold nested patterns. `token` is reported for invalid forms.
+fn parse_pattern(token: &Token, node: Node) -> Result<Pattern, ParseError<Token>> {
+    match node {
+        Node::Literal(literal) => Ok(Pattern::Literal(literal)),
+        Node::VariableCall(vc) if vc.name.token == "_" => Ok(Pattern::Wildcard(vc.name)),
+        Node::VariableCall(vc) => Ok(Pattern::Bind(vc.name)),
+        Node::List(list) => parse_list_pattern(token, list.data),
+        Node::FunctionCall(fc) => match parse_list_pattern(token, fc.arguments)? {
+            Pattern::List { items, rest } => Ok(Pattern::Constructor {
+                name: fc.name,
+                fields: items,
+                rest,
+            }),
+            _ => Err(ParseError::PatternDefinition(token.clone())),
+        },
+        Node::Map(map) => {
+            let mut entries: Stack<(Token, Pattern)> = Stack::new();
+            let mut data = map.data;
+
+            while let (Some(key), Some(value)) = (data.pop_front(), data.pop_front()) {
+                match key {
+                    Node::Literal(key) => entries.push((key, parse_pattern(token, value)?)),
+                    _ => return Err(ParseError::PatternDefinition(token.clone())),
+                }
+            }
+
+            Ok(Pattern::Map { entries })
+        }
+        _ => Err(ParseError::PatternDefinition(token.clone())),
+    }
+}
+
+/// Fails on the first name of `pattern` already in `bound`, adding the others.
+/// A name bound twice would otherwise silently take the later value.
+fn bind_once(pattern: &Pattern, bound: &mut Vec<String>) -> Result<(), ParseError<Token>> {
+    match pattern {
+        Pattern::Bind(name) if bound.contains(&name.token) => {
+            Err(ParseError::DuplicateBinding(name.clone()))
+        }
+        Pattern::Bind(name) => {
+            bound.push(name.token.clone());
+            Ok(())
+        }
+        Pattern::Wildcard(_) | Pattern::Literal(_) => Ok(()),
+        Pattern::List { items, rest }
+        | Pattern::Constructor {
+            fields: items,
+            rest,
+            ..
+        } => {
+            for item in items.data.iter().chain(rest.as_deref()) {
+                bind_once(item, bound)?;
+            }
+
+            Ok(())
+        }
+        Pattern::Map { entries } => {
+            for (_, pattern) in entries.data.iter() {
+                bind_once(pattern, bound)?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn parse_list_pattern(token: &Token, nodes: Stack<Node>) -> Result<Pattern, ParseError<Token>> {
+    let mut items: Stack<Pattern> = Stack::new();
+    let mut nodes = nodes;
+
+    while let Some(node) = nodes.pop_front() {
+        match node {
+            Node::VariableCall(vc) if vc.name.token == "&" => {
+                let rest = nodes
+                    .pop_front()
+                    .ok_or(ParseError::PatternDefinition(vc.name.clone()))?;
+
+                if !nodes.is_empty() {
+                    return Err(ParseError::PatternDefinition(vc.name));
+                }
+
+                return Ok(Pattern::List {
+                    items,
+                    rest: Some(Box::new(parse_pattern(token, rest)?)),
+                });
+            }
+            node => items.push(parse_pattern(token, node)?),
+        }
+    }
+
+    Ok(Pattern::List { items, rest: None })
+}
+
+fn parse_match(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut forms = parse_body(tokens, "No closing bracket for match")?;
+
+    let value = forms
+        .pop_front()
+        .ok_or(ParseError::MatchDefinition(token.clone()))?;
+
+    let mut clauses: Stack<MatchClause> = Stack::new();
+
+    while let Some(form) = forms.pop_front() {
+        let mut body = match form {
+            Node::List(list) if list.data.data.len() >= 2 => list.data,
+            _ => return Err(ParseError::MatchDefinition(token)),
+        };
+
+        let pattern = match body.pop_front() {
+            Some(node) => parse_pattern(&token, node)?,
+            None => return Err(ParseError::MatchDefinition(token)),
+        };
+
+        bind_once(&pattern, &mut Vec::new())?;
+
+        let guard = match body.first() {
+            Some(Node::Literal(keyword)) if keyword.token == ":when" => {
+                body.pop_front();
+                body.pop_front().map(Box::new)
+            }
+            _ => None,
+        };
+
+        if body.is_empty() {
+            return Err(ParseError::MatchDefinition(token));
+        }
+
+        clauses.push(MatchClause {
+            pattern,
+            guard,
+            body,
+        });
+    }
+
+    Ok(Node::Match(Match {
+        token,
+        value: Box::new(value),
+        clauses,
+    }))
+}
+
+/// Reads a try body, peeling a trailing `(finally ...)` and then `(catch e ...)`
+/// off the forms.
+fn parse_try(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut body = parse_body(tokens, "No closing bracket for try")?;
+
+    let mut finally: Stack<Node> = Stack::new();
+
+    if let Some(Node::FunctionCall(fc)) = body.last() {
+        if fc.name.token == "finally" {
+            if let Some(Node::FunctionCall(fc)) = body.pop() {
+                finally = fc.arguments;
+            }
+        }
+    }
+
+    let mut catch = None;
+
+    if let Some(Node::FunctionCall(fc)) = body.last() {
+        if fc.name.token == "catch" {
+            if let Some(Node::FunctionCall(mut fc)) = body.pop() {
+                let name = match fc.arguments.pop_front() {
+                    Some(Node::VariableCall(vc)) => vc.name,
+                    _ => return Err(ParseError::TryDefinition(fc.name)),
+                };
+
+                catch = Some(Catch {
+                    name,
+                    body: fc.arguments,
+                });
+            }
+        }
+    }
+
+    let misplaced = body.data.iter().find_map(|node| match node {
+        Node::FunctionCall(fc) if fc.name.token == "catch" || fc.name.token == "finally" => {
+            Some(fc.name.clone())
+        }
+        _ => None,
+    });
+
+    if let Some(clause) = misplaced {
+        return Err(ParseError::TryDefinition(clause));
+    }
+
+    Ok(Node::Try(Try {
+        token,
+        body,
+        catch,
+        finally,
+    }))
+}
+
+fn parse_handler_bind(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let _left_bracket = expect_closing(
+        tokens,
+        "No left bracket for handlers",
+        TokenKind::LeftSquareBracket,
+    )
+    .map_err(|_| ParseError::HandlerDefinition(token.clone()))?;
+
+    let handlers = parse_list_items(tokens)?;
+
+    if !handlers.data.len().is_multiple_of(2) {
+        return Err(ParseError::HandlerDefinition(token));
+    }
+
+    let body = parse_body(tokens, "No closing bracket for handler-bind")?;
+
+    Ok(Node::HandlerBind(HandlerBind {
+        token,
+        handlers,
+        body,
+    }))
+}
+
+fn parse_restart_case(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let body = tokens.pop_front().ok_or(ParseError::StackError {
+        name: "No expression for restart-case",
+        stack: tokens.clone(),
+    })?;
+
+    let body = match body.token_kind {
+        TokenKind::RightRoundBracket => return Err(ParseError::RestartDefinition(token)),
+        _ => parse_node(body, tokens)?,
+    };
+
+    let mut restarts: Stack<RestartClause> = Stack::new();
+
+    loop {
+        let clause = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for restart-case",
             stack: tokens.clone(),
+        })?;
+
+        match clause.token_kind {
+            TokenKind::RightRoundBracket => break,
+            TokenKind::LeftRoundBracket => {}
+            _ => return Err(ParseError::RestartDefinition(clause)),
+        }
+
+        let name = match tokens.pop_front() {
+            Some(name) if name.token_kind == TokenKind::Symbol => name,
+            _ => return Err(ParseError::RestartDefinition(clause)),
+        };
+
+        let parameters = parse_parameters(tokens)?;
+        let body = parse_body(tokens, "No closing bracket for restart")?;
+
+        restarts.push(RestartClause {
+            name,
+            arity: Arity { parameters, body },
         });
     }
+
+    Ok(Node::RestartCase(RestartCase {
+        token,
+        body: Box::new(body),
+        restarts,
+    }))
+}
+
+fn parse_logical(operator: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let operands = parse_body(tokens, "No closing bracket for logical expression")?;
+
+    Ok(Node::Logical(Logical { operator, operands }))
+}
+
+/// Reads `[name value ...]` into the bound names and their value forms.
+fn parse_bindings(
+    token: &Token,
+    tokens: &mut Stack<Token>,
+) -> Result<(Stack<Token>, Stack<Node>), ParseError<Token>> {
+    let _left_bracket = expect_closing(
+        tokens,
+        "No left bracket for bindings",
+        TokenKind::LeftSquareBracket,
+    )
+    .map_err(|_| ParseError::BindingDefinition(token.clone()))?;
+
+    let mut names: Stack<Token> = Stack::new();
+    let mut values: Stack<Node> = Stack::new();
+
+    loop {
+        let name = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for bindings",
+            stack: tokens.clone(),
+        })?;
+
+        match name.token_kind {
+            TokenKind::RightSquareBracket => break,
+            TokenKind::Symbol => names.push(name),
+            _ => return Err(ParseError::BindingDefinition(name)),
+        }
+
+        let value = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No value for binding",
+            stack: tokens.clone(),
+        })?;
+
+        match value.token_kind {
+            TokenKind::RightSquareBracket | TokenKind::RightRoundBracket => {
+                return Err(ParseError::BindingDefinition(value))
+            }
+            _ => values.push(parse_node(value, tokens)?),
+        }
+    }
+
+    Ok((names, values))
+}
+
+fn parse_while(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let mut body = parse_body(tokens, "No closing bracket for while")?;
+
+    let condition = body
+        .pop_front()
+        .ok_or(ParseError::WhileDefinition(token.clone()))?;
+
+    Ok(Node::While(While {
+        token,
+        condition: Box::new(condition),
+        body,
+    }))
+}
+
+fn parse_loop(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let (names, values) = parse_bindings(&token, tokens)?;
+
+    let body = parse_body(tokens, "No closing bracket for loop")?;
+
+    Ok(Node::Loop(Loop {
+        token,
+        names,
+        values,
+        body,
+    }))
+}
+
+fn parse_recur(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    let arguments = parse_body(tokens, "No closing bracket for recur")?;
+
+    Ok(Node::Recur(Recur { token, arguments }))
+}
+
+/// Reads the single `[name source]` binding and body shared by `dotimes` and `doseq`.
+fn parse_iteration(
+    token: Token,
+    tokens: &mut Stack<Token>,
+) -> Result<Iteration, ParseError<Token>> {
+    let (mut names, mut values) = parse_bindings(&token, tokens)?;
+
+    if names.data.len() != 1 {
+        return Err(ParseError::BindingDefinition(token));
+    }
+
+    let (Some(name), Some(source)) = (names.pop_front(), values.pop_front()) else {
+        return Err(ParseError::BindingDefinition(token));
+    };
+
+    let body = parse_body(tokens, "No closing bracket for iteration")?;
+
+    Ok(Iteration {
+        token,
+        name,
+        source: Box::new(source),
+        body,
+    })
+}
+
+/// Ensures every `recur` sits in tail position of a `loop` and passes one value
+/// per binding. `target` is the binding count of the loop `node` is the tail of.
+fn check_recur(node: &Node, target: Option<usize>) -> Result<(), ParseError<Token>> {
+    match node {
+        Node::Recur(recur) => {
+            let expected = target.ok_or_else(|| ParseError::RecurPosition(recur.token.clone()))?;
+
+            if recur.arguments.data.len() != expected {
+                return Err(ParseError::RecurArity {
+                    token: recur.token.clone(),
+                    expected,
+                    found: recur.arguments.data.len(),
+                });
+            }
+
+            check_all(&recur.arguments)
+        }
+        Node::Loop(lp) => {
+            check_all(&lp.values)?;
+            check_tail(&lp.body, Some(lp.names.data.len()))
+        }
+        Node::Conditional(conditional) => {
+            check_recur(&conditional.condition, None)?;
+            check_tail(&conditional.true_expression, target)?;
+            check_tail(&conditional.false_expression, target)
+        }
+        Node::Match(mt) => {
+            check_recur(&mt.value, None)?;
+
+            for clause in mt.clauses.data.iter() {
+                if let Some(guard) = &clause.guard {
+                    check_recur(guard, None)?;
+                }
+
+                check_tail(&clause.body, target)?;
+            }
+
+            Ok(())
+        }
+        Node::Try(tr) => {
+            check_all(&tr.body)?;
+
+            if let Some(catch) = &tr.catch {
+                check_all(&catch.body)?;
+            }
+
+            check_all(&tr.finally)
+        }
+        Node::HandlerBind(hb) => {
+            check_all(&hb.handlers)?;
+            check_all(&hb.body)
+        }
+        Node::RestartCase(rc) => {
+            check_recur(&rc.body, None)?;
+
+            for clause in rc.restarts.data.iter() {
+                check_all(&clause.arity.body)?;
+            }
+
+            Ok(())
+        }
+        Node::Logical(logical) => check_tail(&logical.operands, target),
+        Node::While(wl) => {
+            check_recur(&wl.condition, None)?;
+            check_all(&wl.body)
+        }
+        Node::DoTimes(iteration) | Node::DoSeq(iteration) => {
+            check_recur(&iteration.source, None)?;
+            check_all(&iteration.body)
+        }
+        Node::FunctionDefinition(fd) => check_arities(&fd.arities),
+        Node::Lambda(lambda) => check_arities(&lambda.arities),
+        Node::MultiDefinition(md) => check_recur(&md.dispatch, None),
+        Node::MethodDefinition(md) => {
+            check_recur(&md.value, None)?;
+            check_arities(&md.arities)
+        }
+        Node::VariableDefinition(vd) => check_recur(&vd.assignment, None),
+        Node::FunctionCall(fc) => check_all(&fc.arguments),
+        Node::List(List { data }) | Node::Map(Map { data }) | Node::Set(Set { data }) => {
+            check_all(data)
+        }
+        Node::Literal(_)
+        | Node::VariableCall(_)
+        | Node::StructDefinition(_)
+        | Node::EnumDefinition(_)
+        | Node::Namespace(_)
+        | Node::Require(_)
+        | Node::Documentation(_) => Ok(()),
+    }
+}
+
+fn check_arities(arities: &Stack<Arity>) -> Result<(), ParseError<Token>> {
+    for arity in arities.data.iter() {
+        let parameters = &arity.parameters;
+
+        for (_, default) in parameters
+            .optional
+            .data
+            .iter()
+            .chain(parameters.keywords.data.iter())
+        {
+            if let Some(default) = default {
+                check_recur(default, None)?;
+            }
+        }
+
+        check_all(&arity.body)?;
+    }
+
+    Ok(())
+}
+
+fn check_all(nodes: &Stack<Node>) -> Result<(), ParseError<Token>> {
+    nodes
+        .data
+        .iter()
+        .try_for_each(|node| check_recur(node, None))
+}
+
+/// Only the last node of a body is in tail position.
+fn check_tail(body: &Stack<Node>, target: Option<usize>) -> Result<(), ParseError<Token>> {
+    let last = body.data.len().saturating_sub(1);
+
+    body.data
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, node)| check_recur(node, if index == last { target } else { None }))
 }
 
 fn parse_var_call(token: Token) -> Result<Node, ParseError<Token>> {
@@ -144,8 +1267,6 @@ fn parse_var_call(token: Token) -> Result<Node, ParseError<Token>> {
 }
 
 fn parse_expression(tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
-    let mut nested_level = 1;
-
     let name = tokens
         .pop_front()
         .ok_or(ParseError::StackError {
@@ -154,31 +1275,46 @@ fn parse_expression(tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>
         })
         .and_then(|name| match name.token_kind {
             TokenKind::Symbol => Ok(name),
-            _ => return Err(ParseError::FunctionCallName(name)),
+            _ => Err(ParseError::FunctionCallName(name)),
         })?;
 
-    let mut arg_vec: Stack<Node> = Stack::new();
+    match name.token.as_str() {
+        "fn" => return parse_lambda(name, tokens),
+        "defstruct" => return parse_struct_definition(name, tokens).map(Node::StructDefinition),
+        "defenum" => return parse_enum_definition(name, tokens),
+        "defn-" => return parse_function_definition(tokens, true),
+        "ns" => return parse_namespace(name, tokens),
+        "require" => return parse_require(name, tokens),
+        "defmulti" => return parse_multi_definition(name, tokens),
+        "defmethod" => return parse_method_definition(name, tokens),
+        "and" | "or" => return parse_logical(name, tokens),
+        "cond" => return parse_cond(name, tokens),
+        "when" | "unless" => return parse_when(name, tokens),
+        "case" => return parse_case(name, tokens),
+        "match" => return parse_match(name, tokens),
+        "try" => return parse_try(name, tokens),
+        "handler-bind" => return parse_handler_bind(name, tokens),
+        "restart-case" => return parse_restart_case(name, tokens),
+        "while" => return parse_while(name, tokens),
+        "loop" => return parse_loop(name, tokens),
+        "recur" => return parse_recur(name, tokens),
+        "dotimes" => return parse_iteration(name, tokens).map(Node::DoTimes),
+        "doseq" => return parse_iteration(name, tokens).map(Node::DoSeq),
+        _ => {}
+    }
 
-    while let Some(token_arg) = tokens.pop_front() {
-        match token_arg.token_kind {
-            TokenKind::Symbol => arg_vec.push(Node::VariableCall(VariableCall { name: token_arg })),
+    let mut arg_vec: Stack<Node> = Stack::new();
 
-            TokenKind::Bool | TokenKind::Integer | TokenKind::StringLiteral => {
-                arg_vec.push(Node::Literal(token_arg))
-            }
-            TokenKind::LeftRoundBracket => {
-                nested_level += 1;
-                let res = parse_expression(tokens)?;
-                arg_vec.push(res)
-            }
-            TokenKind::RightRoundBracket => {
-                nested_level -= 1;
+    loop {
+        let token_arg = tokens.pop_front().ok_or(ParseError::StackError {
+            name: "No closing bracket for expression",
+            stack: tokens.clone(),
+        })?;
 
-                if nested_level == 0 {
-                    break;
-                }
-            }
-            _ => return Err(ParseError::FunctionCallArg(token_arg)),
+        match token_arg.token_kind {
+            TokenKind::RightRoundBracket => break,
+            TokenKind::RightSquareBracket => return Err(ParseError::FunctionCallArg(token_arg)),
+            _ => arg_vec.push(parse_node(token_arg, tokens)?),
         }
     }
 
@@ -190,18 +1326,37 @@ fn parse_expression(tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>
     Ok(Node::FunctionCall(fc))
 }
 
+/// Reads a single node starting at `token`, consuming any nested forms it opens.
+pub fn parse_node(token: Token, tokens: &mut Stack<Token>) -> Result<Node, ParseError<Token>> {
+    match token.token_kind {
+        TokenKind::VariableDefinition => parse_variable_definition(tokens),
+        TokenKind::LeftSquareBracket => parse_list(tokens),
+        TokenKind::LeftCurlyBracket => parse_map(token, tokens),
+        TokenKind::LeftSetBracket => parse_set(tokens),
+        TokenKind::FunctionDefinition => parse_function_definition(tokens, false),
+        TokenKind::Conditional => parse_conditional(token, tokens),
+        TokenKind::LeftRoundBracket => parse_expression(tokens),
+        TokenKind::Symbol => parse_var_call(token),
+        TokenKind::Bool
+        | TokenKind::Nil
+        | TokenKind::Keyword
+        | TokenKind::Integer
+        | TokenKind::Float
+        | TokenKind::StringLiteral
+        | TokenKind::DocString => Ok(Node::Literal(token)),
+        _ => Err(ParseError::ParseError(token)),
+    }
+}
+
 pub fn parse(tokens: &mut Stack<Token>) -> Result<Stack<Node>, ParseError<Token>> {
     let mut nodes: Stack<Node> = Stack::new();
 
     while let Some(token) = tokens.pop_front() {
-        match token.token_kind {
-            TokenKind::VariableDefinition => nodes.push(parse_variable_definition(tokens)?),
-            TokenKind::LeftSquareBracket => nodes.push(parse_list(tokens)?),
-            TokenKind::FunctionDefinition => nodes.push(parse_function_definition(tokens)?),
-            TokenKind::LeftRoundBracket => nodes.push(parse_expression(tokens)?),
-            TokenKind::Symbol => nodes.push(parse_var_call(token)?),
-            _ => return Err(ParseError::ParseError(token)),
-        };
+        let node = parse_node(token, tokens)?;
+
+        check_recur(&node, None)?;
+
+        nodes.push(node);
     }
 
     Ok(nodes)
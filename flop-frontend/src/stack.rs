@@ -38,6 +38,12 @@ impl<K> Stack<K> {
     }
 }
 
+impl<K> Default for Stack<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<K: fmt::Display> fmt::Display for Stack<K> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut elements = self
@@ -1,5 +1,8 @@
 use miette::{MietteSpanContents, SourceCode, SourceOffset, SourceSpan};
-use std::{fmt, path::PathBuf};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
 use thiserror::Error;
 
 #[derive(Debug, Clone, Error)]
@@ -64,7 +67,7 @@ impl Token {
         row: usize,
         offset: usize,
         length: usize,
-        namespace: &PathBuf,
+        namespace: &Path,
     ) -> Self {
         Self {
             token: token.to_string(),
@@ -72,7 +75,7 @@ impl Token {
             row,
             offset,
             length,
-            namespace: namespace.clone(),
+            namespace: namespace.to_path_buf(),
         }
     }
 }
@@ -82,14 +85,20 @@ pub enum TokenKind {
     Space,
     Comment,
     Integer,
+    Float,
     Symbol,
     StringLiteral,
     Bool,
+    Nil,
     Conditional,
     LeftRoundBracket,
     RightRoundBracket,
     LeftSquareBracket,
     RightSquareBracket,
+    LeftCurlyBracket,
+    RightCurlyBracket,
+    LeftSetBracket,
+    Keyword,
     FunctionDefinition,
     VariableDefinition,
     DocString,
@@ -0,0 +1,181 @@
+use crate::{
+    env::Environment,
+    error::EvalError,
+    evaluation::apply,
+    function::Function,
+    value::{ErrorValue, Value},
+};
+use flop_frontend::token::Token;
+use std::rc::Rc;
+
+/// A way out of an error, established by `restart-case` or offered where the
+/// error was signalled.
+#[derive(Debug, Clone)]
+pub struct Restart {
+    pub id: usize,
+    pub name: String,
+    pub description: String,
+    pub takes_value: bool,
+}
+
+/// A function called with the condition when an error of `kind` is signalled.
+/// The kind `error` matches every condition.
+#[derive(Debug, Clone)]
+pub struct Handler {
+    pub token: Token,
+    pub kind: String,
+    pub function: Function,
+}
+
+#[derive(Debug, Clone)]
+pub enum HandlerFrame {
+    Bind(Vec<Handler>),
+    /// A `try` with a `catch` clause, which takes every error signalled inside it.
+    Catch,
+}
+
+/// Asks the user how to recover from an error nobody handled, returning the id of
+/// the chosen restart and its arguments, or `None` to let the error unwind.
+pub type Debugger = fn(&mut Environment, &EvalError, &[Restart]) -> Option<(usize, Vec<Value>)>;
+
+/// How a signalled error was recovered from at the point it happened.
+pub enum Resolution {
+    /// The failed expression evaluates to this value instead.
+    UseValue(Value),
+    /// Evaluate the failed expression again.
+    Retry,
+}
+
+/// The value handlers and `catch` clauses see for an error.
+pub fn condition_value(err: &EvalError) -> Value {
+    match err {
+        EvalError::Thrown { value, .. } => value.as_ref().clone(),
+        native => Value::Error(Rc::new(ErrorValue::from_native(native.clone()))),
+    }
+}
+
+/// The `:type` of an error value's data, or the keyword itself when one was thrown.
+fn condition_type(condition: &Value) -> Option<&str> {
+    match condition {
+        Value::Error(error) => match &error.data {
+            Value::Map(data) => match data.get(&Value::Keyword("type".to_string())) {
+                Some(Value::Keyword(kind)) => Some(kind),
+                _ => None,
+            },
+            _ => None,
+        },
+        Value::Keyword(kind) => Some(kind),
+        _ => None,
+    }
+}
+
+/// Runs the handlers for `err` from the innermost out. Returns whether a `try`
+/// will catch the error, which stops the search.
+fn run_handlers(err: &EvalError, env: &mut Environment) -> Result<bool, EvalError> {
+    let condition = condition_value(err);
+
+    for index in (0..env.handlers.len()).rev() {
+        let handlers = match &env.handlers[index] {
+            HandlerFrame::Catch => return Ok(true),
+            HandlerFrame::Bind(handlers) => handlers.clone(),
+        };
+
+        for handler in handlers {
+            let applies = handler.kind == "error"
+                || condition_type(&condition).is_some_and(|kind| kind == handler.kind);
+
+            if !applies {
+                continue;
+            }
+
+            // a handler runs with its own frame and every inner one disabled
+            let disabled = env.handlers.split_off(index);
+            let result = apply(
+                &handler.function,
+                &handler.token,
+                vec![condition.clone()],
+                env,
+            );
+            env.handlers.extend(disabled);
+
+            result?;
+        }
+    }
+
+    Ok(false)
+}
+
+/// Signals a fresh error: handlers run before anything unwinds and may invoke a
+/// restart, otherwise the debugger is asked if one is installed. The error keeps
+/// unwinding when nobody recovers from it.
+pub fn signal(err: EvalError, env: &mut Environment) -> Result<Resolution, EvalError> {
+    let base = env.restarts.len();
+
+    // a use-value restart established around the error by the program takes priority
+    let use_value = (!env
+        .restarts
+        .iter()
+        .any(|restart| restart.name == "use-value"))
+    .then(|| {
+        env.establish(
+            "use-value",
+            "Use a value instead of the failed expression".to_string(),
+            true,
+        )
+    });
+
+    let define = match &err {
+        EvalError::FunctionCallMissing(name) => Some(env.establish(
+            "define",
+            format!("Define {} now and try again", name.token),
+            true,
+        )),
+        _ => None,
+    };
+
+    let result = match run_handlers(&err, env) {
+        Ok(false) => match env.debugger {
+            Some(debugger) => {
+                let restarts = env.restarts.clone();
+
+                match debugger(env, &err, &restarts) {
+                    Some((id, args)) => {
+                        let name = restarts
+                            .iter()
+                            .find(|restart| restart.id == id)
+                            .map(|restart| restart.name.clone())
+                            .unwrap_or_default();
+
+                        env.restart_args = args;
+                        Err(EvalError::RestartInvoked { name, id })
+                    }
+                    None => Ok(()),
+                }
+            }
+            None => Ok(()),
+        },
+        Ok(true) => Ok(()),
+        Err(err) => Err(err),
+    };
+
+    env.restarts.truncate(base);
+
+    match result {
+        Err(EvalError::RestartInvoked { id, .. }) if Some(id) == use_value => {
+            let value = std::mem::take(&mut env.restart_args).into_iter().next();
+            Ok(Resolution::UseValue(value.unwrap_or(Value::Nil)))
+        }
+        Err(EvalError::RestartInvoked { id, .. }) if Some(id) == define => {
+            env.restart_args.clear();
+            Ok(Resolution::Retry)
+        }
+        Err(other) => {
+            env.unwinding = true;
+            Err(other)
+        }
+        Ok(()) => {
+            env.unwinding = true;
+            Err(err)
+        }
+    }
+}
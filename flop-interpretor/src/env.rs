@@ -1,17 +1,349 @@
-use flop_frontend::ast::{FunctionDefinition, VariableDefinition};
-use std::collections::HashMap;
+use crate::{
+    condition::{Debugger, HandlerFrame, Restart},
+    error::EvalError,
+    function::{Function, MultiMethod},
+    native::IntoNative,
+    stdlib,
+    structure::StructType,
+    value::Value,
+};
+use flop_frontend::{checker::Checker, error::ParseWarning, token::Token};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    rc::Rc,
+};
+
+/// Nested non-tail calls allowed before evaluation fails with a stack depth error.
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// Native stack assumed to be available when the host doesn't say, the size Rust
+/// gives spawned threads.
+pub const DEFAULT_STACK_SIZE: usize = 2 * 1024 * 1024;
+
+/// Local bindings introduced by a function call or a binding form.
+#[derive(Debug, Clone)]
+pub struct Scope {
+    pub bindings: HashMap<String, Value>,
+    /// Function calls start a new lexical boundary: lookups don't see past it
+    /// into the caller's locals.
+    pub boundary: bool,
+}
+
+/// What a source file declared with `ns` and `require`.
+#[derive(Debug, Clone, Default)]
+pub struct Module {
+    /// Set by `ns`, global definitions made in the file are qualified with it.
+    pub name: Option<String>,
+    /// Module names by the alias given in `(require name :as alias)`.
+    pub aliases: HashMap<String, String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Environment {
-    pub functions: HashMap<String, FunctionDefinition>,
-    pub variables: HashMap<String, VariableDefinition>,
+    pub functions: HashMap<String, Function>,
+    /// Functions implemented in Rust, the builtins and those registered by a host.
+    /// Looked up after the functions defined in Flop.
+    pub natives: HashMap<String, Function>,
+    pub variables: HashMap<String, Value>,
+    /// Types declared with `defstruct` and the variants of `defenum`, by name.
+    pub types: HashMap<String, Rc<StructType>>,
+    /// Dispatch tables of the functions declared with `defmulti`, by name.
+    pub multimethods: HashMap<String, MultiMethod>,
+    /// Modules by the path of the file declaring them, as found in `Token.namespace`.
+    pub modules: HashMap<PathBuf, Module>,
+    /// Names of the modules already loaded, which `require` doesn't load again.
+    pub loaded: HashSet<String>,
+    /// Modules being loaded by nested requires, outermost first.
+    pub loading: Vec<String>,
+    /// Qualified names of the functions declared with `defn-`.
+    pub private: HashSet<String>,
+    /// Directories searched for required modules after the requiring file's own.
+    pub search_path: Vec<PathBuf>,
+    pub scopes: Vec<Scope>,
+    pub depth: usize,
+    pub max_depth: usize,
+    /// Native stack of the thread evaluating. Nested calls fail with a stack depth
+    /// error once they have used most of it, whatever `max_depth` allows.
+    pub stack_size: usize,
+    /// Stack position of the outermost call being evaluated.
+    stack_base: Option<usize>,
+    pub handlers: Vec<HandlerFrame>,
+    /// Active restarts, innermost last.
+    pub restarts: Vec<Restart>,
+    /// Arguments passed to the restart currently being invoked.
+    pub restart_args: Vec<Value>,
+    next_restart: usize,
+    /// Set once an error has been signalled, so outer expressions it unwinds
+    /// through don't signal it again.
+    pub unwinding: bool,
+    pub debugger: Option<Debugger>,
+    /// Checks forms before they are evaluated, knowing the enums defined so far.
+    pub checker: Checker,
+    /// What the checker found in the forms evaluated so far, until the host takes
+    /// the warnings to report them.
+    pub warnings: Vec<ParseWarning>,
 }
 
 impl Environment {
     pub fn new() -> Self {
-        Self {
+        let mut env = Self {
             functions: HashMap::new(),
+            natives: HashMap::new(),
             variables: HashMap::new(),
+            types: HashMap::new(),
+            multimethods: HashMap::new(),
+            modules: HashMap::new(),
+            loaded: HashSet::new(),
+            loading: Vec::new(),
+            private: HashSet::new(),
+            search_path: Vec::new(),
+            scopes: Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            stack_size: DEFAULT_STACK_SIZE,
+            stack_base: None,
+            handlers: Vec::new(),
+            restarts: Vec::new(),
+            restart_args: Vec::new(),
+            next_restart: 0,
+            unwinding: false,
+            debugger: None,
+            checker: Checker::new(),
+            warnings: Vec::new(),
+        };
+
+        stdlib::register(&mut env);
+
+        env
+    }
+
+    /// Counts a nested call, failing when it would exceed `max_depth` or leave too
+    /// little native stack. Every successful call is paired with
+    /// [`Environment::leave_call`].
+    pub fn enter_call(&mut self, token: &Token) -> Result<(), EvalError> {
+        // a quarter of the stack is left for the frames below the outermost
+        // call and those one level of evaluation uses between checks
+        let used = self
+            .stack_base
+            .map_or(0, |base| base.saturating_sub(stack_position()));
+
+        if self.depth >= self.max_depth || used > self.stack_size - self.stack_size / 4 {
+            return Err(EvalError::StackDepthExceeded {
+                token: token.clone(),
+                depth: self.depth,
+            });
+        }
+
+        if self.stack_base.is_none() {
+            self.stack_base = Some(stack_position());
+        }
+
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    pub fn leave_call(&mut self) {
+        self.depth -= 1;
+
+        if self.depth == 0 {
+            self.stack_base = None;
+        }
+    }
+
+    /// Makes a Rust function callable from Flop as `name`, replacing any native
+    /// of that name. Typed parameters and return values are converted with
+    /// `FromValue` and `IntoValue`, a function taking `&[Value]` accepts any number
+    /// of arguments, and returning a `Result` reports its error where Flop called it.
+    ///
+    /// ```
+    /// # use flop_interpretor::{env::Environment, value::Value};
+    /// let mut env = Environment::new();
+    ///
+    /// env.register_fn("add", |a: i64, b: i64| a + b);
+    /// env.register_fn("count", |args: &[Value]| args.len());
+    /// env.register_fn("read-file", |path: String| std::fs::read_to_string(path));
+    /// ```
+    pub fn register_fn<Marker>(&mut self, name: &str, function: impl IntoNative<Marker>) {
+        self.natives.insert(
+            name.to_string(),
+            Function::Builtin {
+                name: name.to_string(),
+                builtin: function.into_native(),
+            },
+        );
+    }
+
+    /// Index of the innermost function scope, if evaluation is inside a call.
+    fn boundary(&self) -> Option<usize> {
+        self.scopes.iter().rposition(|scope| scope.boundary)
+    }
+
+    /// The local scopes visible from the innermost one, innermost first.
+    fn visible_scopes(&self) -> impl Iterator<Item = &Scope> {
+        let start = self.boundary().unwrap_or(0);
+
+        self.scopes[start..].iter().rev()
+    }
+
+    /// The module the file of `token` declared with `ns`.
+    pub fn module_name(&self, token: &Token) -> Option<&str> {
+        self.modules
+            .get(&token.namespace)
+            .and_then(|module| module.name.as_deref())
+    }
+
+    /// The global name a definition of `name` is stored under, qualified with the
+    /// module of the file it is made in.
+    pub fn qualify(&self, name: &Token) -> String {
+        match self.module_name(name) {
+            Some(module) => format!("{}/{}", module, name.token),
+            None => name.token.clone(),
+        }
+    }
+
+    /// The global names `symbol` can refer to, most specific first. Inside a module
+    /// its own definitions shadow unqualified ones, and `alias/name` refers to a
+    /// public definition of a required module.
+    pub fn global_names(&self, symbol: &Token) -> Result<Vec<String>, EvalError> {
+        let module = self.modules.get(&symbol.namespace);
+        let own = module.and_then(|module| module.name.as_deref());
+
+        let qualified = symbol
+            .token
+            .split_once('/')
+            .filter(|(prefix, name)| !prefix.is_empty() && !name.is_empty());
+
+        let Some((prefix, name)) = qualified else {
+            return Ok(match own {
+                Some(own) => vec![format!("{}/{}", own, symbol.token), symbol.token.clone()],
+                None => vec![symbol.token.clone()],
+            });
+        };
+
+        let target = module
+            .and_then(|module| module.aliases.get(prefix))
+            .map_or(prefix, String::as_str);
+
+        let global = format!("{}/{}", target, name);
+
+        if self.private.contains(&global) && own != Some(target) {
+            return Err(EvalError::ModuleError {
+                token: symbol.clone(),
+                message: format!("{} is private to {}", name, target),
+            });
+        }
+
+        Ok(vec![global])
+    }
+
+    /// Looks `symbol` up among the global definitions in `globals`.
+    pub fn resolve<'a, T>(
+        &self,
+        globals: &'a HashMap<String, T>,
+        symbol: &Token,
+    ) -> Result<Option<&'a T>, EvalError> {
+        // files without modules can only refer to unqualified names
+        if self.modules.is_empty() {
+            return Ok(globals.get(&symbol.token));
+        }
+
+        Ok(self
+            .global_names(symbol)?
+            .iter()
+            .find_map(|name| globals.get(name)))
+    }
+
+    /// Looks a variable up in the visible local scopes, then among the globals
+    /// `symbol` can refer to.
+    pub fn lookup(&self, symbol: &Token) -> Result<Option<&Value>, EvalError> {
+        match self
+            .visible_scopes()
+            .find_map(|scope| scope.bindings.get(&symbol.token))
+        {
+            Some(value) => Ok(Some(value)),
+            None => self.resolve(&self.variables, symbol),
+        }
+    }
+
+    /// Binds `name` in the innermost scope, or globally at the top level.
+    pub fn define(&mut self, name: String, value: Value) {
+        match self.scopes.last_mut() {
+            Some(scope) => scope.bindings.insert(name, value),
+            None => self.variables.insert(name, value),
+        };
+    }
+
+    /// Updates the nearest visible binding of `name`, as `setq` does. New names are
+    /// bound in the enclosing function's scope, or globally at the top level, so
+    /// loop bodies can update the variables around them.
+    pub fn assign(&mut self, name: &Token, value: Value) {
+        let boundary = self.boundary();
+
+        let existing = self.scopes[boundary.unwrap_or(0)..]
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.bindings.contains_key(&name.token));
+
+        match (existing, boundary) {
+            (Some(scope), _) => scope.bindings.insert(name.token.clone(), value),
+            (None, Some(index)) => self.scopes[index]
+                .bindings
+                .insert(name.token.clone(), value),
+            (None, None) => self.variables.insert(self.qualify(name), value),
+        };
+    }
+
+    pub fn push_scope(&mut self, bindings: HashMap<String, Value>, boundary: bool) {
+        self.scopes.push(Scope { bindings, boundary });
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Makes a restart available until the restarts are truncated below it again.
+    pub fn establish(&mut self, name: &str, description: String, takes_value: bool) -> usize {
+        let id = self.next_restart;
+        self.next_restart += 1;
+
+        self.restarts.push(Restart {
+            id,
+            name: name.to_string(),
+            description,
+            takes_value,
+        });
+
+        id
+    }
+
+    /// Flattens the visible local bindings for a closure to capture.
+    pub fn captured(&self) -> HashMap<String, Value> {
+        let mut captured = HashMap::new();
+
+        for scope in self.visible_scopes() {
+            for (name, value) in scope.bindings.iter() {
+                captured
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
         }
+
+        captured
     }
 }
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The address of a local in a frame of its own, the stack grows down from the
+/// outermost call's.
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0_u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
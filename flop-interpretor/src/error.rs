@@ -1,9 +1,15 @@
+use crate::value::Value;
 use flop_frontend::token::Token;
-use miette::Diagnostic;
-use std::num::ParseIntError;
+use miette::{Diagnostic, LabeledSpan, Report, Severity, SourceCode};
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display},
+    num::ParseIntError,
+    sync::Arc,
+};
 use thiserror::Error;
 
-#[derive(Debug, Error, Diagnostic)]
+#[derive(Debug, Clone, Error, Diagnostic)]
 #[error(transparent)]
 pub enum EvalError {
     #[error("evalerror symbolerror")]
@@ -13,18 +19,272 @@ pub enum EvalError {
         Token,
     ),
 
+    #[error("missing function: {}", .0.token)]
     FunctionCallMissing(
         #[source_code]
         #[label("Missing Function Call")]
         Token,
     ),
 
+    #[error("type error: expected {expected}, found {found}")]
+    TypeError {
+        #[source_code]
+        #[label("Argument has the wrong type")]
+        token: Token,
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    #[error("arity error: expected {expected} arguments, found {found}")]
+    ArityError {
+        #[source_code]
+        #[label("Wrong number of arguments")]
+        token: Token,
+        expected: String,
+        found: usize,
+    },
+
+    #[error("index error: index {index} is out of bounds for length {length}")]
+    IndexError {
+        #[source_code]
+        #[label("Index out of bounds")]
+        token: Token,
+        index: i64,
+        length: usize,
+    },
+
+    #[error("arithmetic error: {message}")]
+    ArithmeticError {
+        #[source_code]
+        #[label("Arithmetic failed")]
+        token: Token,
+        message: &'static str,
+    },
+
+    #[error("value error: {message}")]
+    ValueError {
+        #[source_code]
+        #[label("Invalid argument")]
+        token: Token,
+        message: String,
+    },
+
+    #[error("stack depth exceeded: more than {depth} nested calls")]
+    StackDepthExceeded {
+        #[source_code]
+        #[label("Call nested too deeply")]
+        token: Token,
+        depth: usize,
+    },
+
+    #[error("match error: no clause matches {value}")]
+    MatchError {
+        #[source_code]
+        #[label("No pattern matched")]
+        token: Token,
+        value: String,
+    },
+
+    #[error("no method: {message}")]
+    NoMethod {
+        #[source_code]
+        #[label("No method for the dispatch value")]
+        token: Token,
+        message: String,
+    },
+
+    /// An error returned by a function registered from Rust.
+    #[error("native error: {message}")]
+    NativeError {
+        #[source_code]
+        #[label("Native function failed")]
+        token: Token,
+        message: String,
+    },
+
+    #[error("module error: {message}")]
+    ModuleError {
+        #[source_code]
+        #[label("While loading this file")]
+        token: Token,
+        message: String,
+    },
+
+    /// A lexer or parser error in a file loaded while evaluating.
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    SourceError(SourceError),
+
+    /// A value raised with `throw`, carried to the `catch` that stops it.
+    #[error("uncaught exception: {}", thrown_text(value))]
+    Thrown {
+        #[source_code]
+        #[label("Thrown here")]
+        token: Token,
+        value: Box<Value>,
+    },
+
+    /// Unwinds to the `restart-case` that established the restart, never caught by `try`.
+    #[error("restart {name} was invoked outside of its restart-case")]
+    RestartInvoked { name: String, id: usize },
+
     #[error("Parsing error: {0}")]
     ParsingError(ParseIntError),
 }
 
+impl EvalError {
+    /// Short name of the error, exposed to Flop code as the `:type` of caught errors.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EvalError::SymbolError(_) => "symbol-error",
+            EvalError::FunctionCallMissing(_) => "unbound-function",
+            EvalError::TypeError { .. } => "type-error",
+            EvalError::ArityError { .. } => "arity-error",
+            EvalError::IndexError { .. } => "index-error",
+            EvalError::ArithmeticError { .. } => "arithmetic-error",
+            EvalError::ValueError { .. } => "value-error",
+            EvalError::StackDepthExceeded { .. } => "stack-depth-exceeded",
+            EvalError::MatchError { .. } => "match-error",
+            EvalError::NoMethod { .. } => "no-method",
+            EvalError::NativeError { .. } => "native-error",
+            EvalError::ModuleError { .. } => "module-error",
+            EvalError::SourceError(_) => "syntax-error",
+            EvalError::Thrown { .. } => "thrown",
+            EvalError::RestartInvoked { .. } => "restart",
+            EvalError::ParsingError(_) => "parsing-error",
+        }
+    }
+
+    /// The token the error points at, if it has one.
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            EvalError::SymbolError(token)
+            | EvalError::FunctionCallMissing(token)
+            | EvalError::TypeError { token, .. }
+            | EvalError::ArityError { token, .. }
+            | EvalError::IndexError { token, .. }
+            | EvalError::ArithmeticError { token, .. }
+            | EvalError::ValueError { token, .. }
+            | EvalError::StackDepthExceeded { token, .. }
+            | EvalError::MatchError { token, .. }
+            | EvalError::NoMethod { token, .. }
+            | EvalError::NativeError { token, .. }
+            | EvalError::ModuleError { token, .. }
+            | EvalError::Thrown { token, .. } => Some(token),
+            EvalError::SourceError(_)
+            | EvalError::RestartInvoked { .. }
+            | EvalError::ParsingError(_) => None,
+        }
+    }
+
+    /// The error as a report that may leave the thread evaluating. A thrown value
+    /// can hold functions of the interpreter, so only what is printed is kept.
+    pub fn into_report(self) -> Report {
+        match self {
+            EvalError::SourceError(err) => Report::new(err),
+            err => Report::new(Reported {
+                message: err.to_string(),
+                labels: err.labels().map(Iterator::collect).unwrap_or_default(),
+                token: err.token().cloned(),
+            }),
+        }
+    }
+}
+
+/// What an uncaught thrown value is reported as, the message of an error value
+/// or the value itself.
+fn thrown_text(value: &Value) -> String {
+    match value {
+        Value::Error(error) => error.message.clone(),
+        other => other.to_string(),
+    }
+}
+
 impl From<ParseIntError> for EvalError {
     fn from(error: ParseIntError) -> Self {
         EvalError::ParsingError(error)
     }
 }
+
+/// Shares a lexer or parser diagnostic, so errors of loaded files stay cheap to
+/// clone while still pointing into the file they came from.
+#[derive(Debug, Clone)]
+pub struct SourceError(Arc<dyn Diagnostic + Send + Sync>);
+
+impl SourceError {
+    pub fn new(diagnostic: impl Diagnostic + Send + Sync + 'static) -> Self {
+        Self(Arc::new(diagnostic))
+    }
+}
+
+impl Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl StdError for SourceError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.0.source()
+    }
+}
+
+impl Diagnostic for SourceError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.code()
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.0.severity()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.0.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.0.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.0.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.0.related()
+    }
+
+    fn diagnostic_source(&self) -> Option<&dyn Diagnostic> {
+        self.0.diagnostic_source()
+    }
+}
+
+/// An evaluation error reduced to its message and the labels on its token.
+#[derive(Debug)]
+struct Reported {
+    message: String,
+    labels: Vec<LabeledSpan>,
+    token: Option<Token>,
+}
+
+impl Display for Reported {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl StdError for Reported {}
+
+impl Diagnostic for Reported {
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.token.as_ref().map(|token| token as &dyn SourceCode)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(self.labels.iter().cloned()))
+    }
+}
@@ -1,106 +1,916 @@
-use crate::{env::Environment, error::EvalError, operation::Operation};
+use crate::{
+    condition::{signal, Handler, HandlerFrame, Resolution},
+    env::Environment,
+    error::EvalError,
+    function::{accepts, expected, unpaired, Closure, Function, MultiMethod},
+    list::List,
+    map::{Map, Set},
+    module,
+    pattern::match_pattern,
+    stdlib::{expect_function, expect_integer, expect_list},
+    structure::{StructOp, StructType},
+    value::{ErrorValue, Value},
+};
 use flop_frontend::{
-    ast::{FunctionCall, Node, VariableDefinition},
+    ast::{
+        Conditional, EnumDefinition, FunctionCall, HandlerBind, Iteration, Logical, Loop, Match,
+        MethodDefinition, MultiDefinition, Node, Parameters, Pattern, RestartCase,
+        StructDefinition, Try, While,
+    },
     stack::Stack,
     token::{Token, TokenKind},
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
 pub enum EvalResult {
-    /// handles base literals like integers, bools and strings
-    Literal(Token),
+    /// handles evaluated values like integers, bools, strings and lists
+    Value(Value),
     Void,
-    List(Stack<Node>),
 }
 
-fn parse_literal(node: Node, env: &mut Environment) -> Result<i64, EvalError> {
-    match evaluate_node(node, env) {
-        Ok(EvalResult::Literal(token)) => Ok(token.token.parse::<i64>()?),
-        _ => unreachable!(),
+pub(crate) fn parse_literal(token: &Token) -> Result<Value, EvalError> {
+    match token.token_kind {
+        TokenKind::Integer => Ok(Value::Integer(token.token.parse::<i64>()?)),
+        TokenKind::Float => token
+            .token
+            .parse::<f64>()
+            .map(Value::Float)
+            .map_err(|_| EvalError::SymbolError(token.clone())),
+        TokenKind::Bool => Ok(Value::Bool(token.token == "true")),
+        TokenKind::Nil => Ok(Value::Nil),
+        TokenKind::Keyword => Ok(Value::Keyword(token.token[1..].to_string())),
+        TokenKind::StringLiteral | TokenKind::DocString => Ok(Value::String(token.token.clone())),
+        _ => Err(EvalError::SymbolError(token.clone())),
     }
 }
 
-fn evaluate_math(fc: &mut FunctionCall, env: &mut Environment) -> Result<EvalResult, EvalError> {
-    let node: Node = fc.arguments.pop_front().unwrap();
+/// Result of evaluating a node in tail position: calls to closures are handed back
+/// to the caller instead of growing the native stack.
+enum Step {
+    Value(Value),
+    TailCall(Function, Token, Vec<Value>),
+    /// New values for the bindings of the enclosing `loop`.
+    Recur(Vec<Value>),
+}
 
-    let mut oper = parse_literal(node, env)?;
+/// Resolves a symbol bound to a user defined function, either as a variable or a `defn`.
+fn lookup_user_function(name: &Token, env: &Environment) -> Result<Option<Function>, EvalError> {
+    if let Some(Value::Function(function)) = env.lookup(name)? {
+        return Ok(Some(function.clone()));
+    }
 
-    let operation = Operation::try_from(&fc.name)?;
+    Ok(env.resolve(&env.functions, name)?.cloned())
+}
 
-    while let Some(operand) = fc.arguments.pop_front() {
-        let oper_val = parse_literal(operand, env)?;
+/// Resolves a symbol in function position, checking user definitions before builtins.
+pub(crate) fn lookup_function(name: &Token, env: &Environment) -> Result<Function, EvalError> {
+    if let Some(function) = lookup_user_function(name, env)? {
+        return Ok(function);
+    }
 
-        oper = operation.apply(oper, oper_val);
+    match env.natives.get(&name.token) {
+        Some(native) => Ok(native.clone()),
+        None => Err(EvalError::FunctionCallMissing(name.clone())),
     }
+}
 
-    let final_token = Token::new(
-        &oper.to_string(),
-        TokenKind::Integer,
-        1,
-        1,
-        oper.to_string().len(),
-        &fc.name.namespace,
-    );
+/// Binds arguments to the declared parameters in the current scope. Defaults are
+/// evaluated after the parameters before them are bound, so they can refer to them.
+fn bind_parameters(
+    parameters: &Parameters,
+    token: &Token,
+    args: Vec<Value>,
+    env: &mut Environment,
+) -> Result<(), EvalError> {
+    let mut args = args.into_iter();
 
-    Ok(EvalResult::Literal(final_token))
-}
+    for (pattern, value) in parameters.required.data.iter().zip(args.by_ref()) {
+        destructure(pattern, value, token, env)?;
+    }
 
-pub fn evaluate_node(mut node: Node, env: &mut Environment) -> Result<EvalResult, EvalError> {
-    match node {
-        Node::FunctionCall(ref mut fc) => match env.functions.get(&fc.name.token) {
-            Some(function) => {
-                let mut local_env = Environment {
-                    functions: env.functions.clone(),
-                    variables: HashMap::new(),
-                };
-
-                for (param, arg) in function
-                    .parameters
+    for (name, default) in parameters.optional.data.iter() {
+        let value = match (args.next(), default) {
+            (Some(value), _) => value,
+            (None, Some(default)) => eval(default, env)?,
+            (None, None) => Value::Nil,
+        };
+
+        env.define(name.token.clone(), value);
+    }
+
+    if let Some(rest) = &parameters.rest {
+        destructure(rest, Value::List(args.by_ref().collect()), token, env)?;
+    }
+
+    if parameters.keywords.is_empty() {
+        return Ok(());
+    }
+
+    let mut given = HashMap::new();
+
+    while let (Some(key), Some(value)) = (args.next(), args.next()) {
+        match key {
+            Value::Keyword(key)
+                if parameters
+                    .keywords
                     .data
                     .iter()
-                    .zip(fc.arguments.data.clone())
-                {
-                    let assignment = match arg {
-                        Node::Literal(token) => token,
-                        _ => unreachable!(),
-                    };
-
-                    local_env.variables.insert(
-                        param.token.clone(),
-                        VariableDefinition {
-                            name: param.clone(),
-                            assignment,
-                        },
-                    );
+                    .any(|(name, _)| name.token == key) =>
+            {
+                given.insert(key, value);
+            }
+            key => {
+                return Err(EvalError::ValueError {
+                    token: token.clone(),
+                    message: format!("unknown keyword argument {}", key),
+                })
+            }
+        }
+    }
+
+    for (name, default) in parameters.keywords.data.iter() {
+        let value = match (given.remove(&name.token), default) {
+            (Some(value), _) => value,
+            (None, Some(default)) => eval(default, env)?,
+            (None, None) => Value::Nil,
+        };
+
+        env.define(name.token.clone(), value);
+    }
+
+    Ok(())
+}
+
+/// The error for keyword arguments that end on a name without its value.
+fn unpaired_keyword(token: &Token) -> EvalError {
+    EvalError::ValueError {
+        token: token.clone(),
+        message: "keyword arguments come in :name value pairs, the last one has no value"
+            .to_string(),
+    }
+}
+
+/// Binds the names in a parameter pattern, failing when the argument doesn't fit it.
+fn destructure(
+    pattern: &Pattern,
+    value: Value,
+    token: &Token,
+    env: &mut Environment,
+) -> Result<(), EvalError> {
+    let mut bindings = HashMap::new();
+
+    if !match_pattern(pattern, &value, &mut bindings, env)? {
+        return Err(EvalError::ValueError {
+            token: token.clone(),
+            message: format!("{} does not match the parameter pattern", value),
+        });
+    }
+
+    for (name, value) in bindings {
+        env.define(name, value);
+    }
+
+    Ok(())
+}
+
+/// Runs the closure arity matching the argument count in a fresh scope holding
+/// its captured variables and parameters.
+fn enter_closure(
+    closure: &Closure,
+    token: &Token,
+    args: Vec<Value>,
+    env: &mut Environment,
+) -> Result<Step, EvalError> {
+    let arity = match closure.select(args.len()) {
+        Some(arity) => arity,
+        None if closure.unpaired(args.len()) => return Err(unpaired_keyword(token)),
+        None => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: closure.expected(),
+                found: args.len(),
+            })
+        }
+    };
+
+    env.push_scope(closure.captured.clone(), true);
+
+    let step = bind_parameters(&arity.parameters, token, args, env)
+        .and_then(|_| evaluate_body(&arity.body, env));
+
+    env.pop_scope();
+
+    step
+}
+
+/// The method of multimethod `name` registered for the value its dispatch function
+/// returns for `args`, falling back to the `:default` method.
+fn select_method(
+    name: &str,
+    token: &Token,
+    args: &[Value],
+    env: &mut Environment,
+) -> Result<Function, EvalError> {
+    let dispatch = match env.multimethods.get(name) {
+        Some(multi) => multi.dispatch.clone(),
+        None => return Err(EvalError::FunctionCallMissing(token.clone())),
+    };
+
+    let value = apply(&dispatch, token, args.to_vec(), env)?;
+
+    let methods = &env.multimethods[name].methods;
+
+    methods
+        .get(&value)
+        .or_else(|| methods.get(&Value::Keyword("default".to_string())))
+        .cloned()
+        .ok_or_else(|| EvalError::NoMethod {
+            token: token.clone(),
+            message: format!("{} has no method for {}", name, value),
+        })
+}
+
+/// Calls a function implemented in Rust.
+fn call_native(
+    function: &Function,
+    token: &Token,
+    args: Vec<Value>,
+    env: &mut Environment,
+) -> Result<Value, EvalError> {
+    match function {
+        Function::Builtin { builtin, .. } => builtin(env, token, args),
+        Function::Struct { kind, op, .. } => op.call(kind, token, args),
+        Function::Closure(_) | Function::Multi { .. } => apply(function, token, args, env),
+    }
+}
+
+/// Calls `function` with already evaluated arguments.
+///
+/// Tail calls made by the body are run in this loop, so only calls in non-tail
+/// position count towards the environment's depth limit.
+pub fn apply(
+    function: &Function,
+    token: &Token,
+    args: Vec<Value>,
+    env: &mut Environment,
+) -> Result<Value, EvalError> {
+    if !matches!(function, Function::Closure(_) | Function::Multi { .. }) {
+        return call_native(function, token, args, env);
+    }
+
+    env.enter_call(token)?;
+
+    let mut call = (function.clone(), token.clone(), args);
+
+    let result = loop {
+        let (function, token, args) = call;
+
+        let step = match &function {
+            Function::Closure(closure) => enter_closure(closure, &token, args, env),
+            Function::Multi { name } => match select_method(name, &token, &args, env) {
+                Ok(method) => Ok(Step::TailCall(method, token, args)),
+                Err(err) => Err(err),
+            },
+            native => break call_native(native, &token, args, env),
+        };
+
+        match step {
+            Ok(Step::Value(value)) => break Ok(value),
+            Ok(Step::TailCall(function, token, args)) => call = (function, token, args),
+            Ok(Step::Recur(_)) => unreachable!("recur is only parsed in tail position of a loop"),
+            Err(err) => break Err(err),
+        }
+    };
+
+    env.leave_call();
+
+    result
+}
+
+/// Natives are called straight away, closures are handed back as a tail call.
+fn evaluate_function_call(fc: &FunctionCall, env: &mut Environment) -> Result<Step, EvalError> {
+    let function = lookup_function(&fc.name, env)?;
+
+    let args = fc
+        .arguments
+        .data
+        .iter()
+        .map(|arg| eval(arg, env))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match function {
+        Function::Closure(_) | Function::Multi { .. } => {
+            Ok(Step::TailCall(function, fc.name.clone(), args))
+        }
+        native => call_native(&native, &fc.name, args, env).map(Step::Value),
+    }
+}
+
+/// Evaluates every node in order; the last one is in tail position.
+fn evaluate_body(body: &Stack<Node>, env: &mut Environment) -> Result<Step, EvalError> {
+    let last = body.data.len().saturating_sub(1);
+
+    for (index, node) in body.data.iter().enumerate() {
+        if index == last {
+            return eval_tail(node, env);
+        }
+
+        eval(node, env)?;
+    }
+
+    Ok(Step::Value(Value::Nil))
+}
+
+fn evaluate_conditional(
+    conditional: &Conditional,
+    env: &mut Environment,
+) -> Result<Step, EvalError> {
+    if eval(&conditional.condition, env)?.is_truthy() {
+        evaluate_body(&conditional.true_expression, env)
+    } else {
+        evaluate_body(&conditional.false_expression, env)
+    }
+}
+
+/// Runs the body of the first clause whose pattern matches and whose guard holds,
+/// with the pattern's bindings in scope.
+fn evaluate_match(mt: &Match, env: &mut Environment) -> Result<Step, EvalError> {
+    let value = eval(&mt.value, env)?;
+
+    for clause in mt.clauses.data.iter() {
+        let mut bindings = HashMap::new();
+
+        if !match_pattern(&clause.pattern, &value, &mut bindings, env)? {
+            continue;
+        }
+
+        env.push_scope(bindings, false);
+
+        let guard = match &clause.guard {
+            Some(guard) => eval(guard, env).map(|result| result.is_truthy()),
+            None => Ok(true),
+        };
+
+        let step = match guard {
+            Ok(true) => evaluate_body(&clause.body, env).map(Some),
+            Ok(false) => Ok(None),
+            Err(err) => Err(err),
+        };
+
+        env.pop_scope();
+
+        if let Some(step) = step? {
+            return Ok(step);
+        }
+    }
+
+    Err(EvalError::MatchError {
+        token: mt.token.clone(),
+        value: value.to_string(),
+    })
+}
+
+/// `and` returns the first falsey operand and `or` the first truthy one, otherwise the
+/// last operand. Remaining operands are not evaluated, the last one is in tail position.
+fn evaluate_logical(logical: &Logical, env: &mut Environment) -> Result<Step, EvalError> {
+    let is_and = logical.operator.token == "and";
+
+    let last = logical.operands.data.len().saturating_sub(1);
+
+    for (index, operand) in logical.operands.data.iter().enumerate() {
+        if index == last {
+            return eval_tail(operand, env);
+        }
+
+        let result = eval(operand, env)?;
+
+        if result.is_truthy() != is_and {
+            return Ok(Step::Value(result));
+        }
+    }
+
+    Ok(Step::Value(Value::Bool(is_and)))
+}
+
+/// Runs `f` in a fresh local scope, popping it again even when `f` fails.
+fn in_scope<T>(
+    env: &mut Environment,
+    f: impl FnOnce(&mut Environment) -> Result<T, EvalError>,
+) -> Result<T, EvalError> {
+    env.push_scope(HashMap::new(), false);
+    let result = f(env);
+    env.pop_scope();
+
+    result
+}
+
+fn evaluate_each(body: &Stack<Node>, env: &mut Environment) -> Result<(), EvalError> {
+    body.data
+        .iter()
+        .try_for_each(|node| eval(node, env).map(|_| ()))
+}
+
+fn evaluate_while(wl: &While, env: &mut Environment) -> Result<Value, EvalError> {
+    while eval(&wl.condition, env)?.is_truthy() {
+        evaluate_each(&wl.body, env)?;
+    }
+
+    Ok(Value::Nil)
+}
+
+/// Binds the loop variables and runs the body until it finishes without `recur`.
+fn evaluate_loop(lp: &Loop, env: &mut Environment) -> Result<Step, EvalError> {
+    in_scope(env, |env| {
+        for (name, value) in lp.names.data.iter().zip(lp.values.data.iter()) {
+            let value = eval(value, env)?;
+            env.define(name.token.clone(), value);
+        }
+
+        loop {
+            match evaluate_body(&lp.body, env)? {
+                Step::Recur(values) => {
+                    for (name, value) in lp.names.data.iter().zip(values) {
+                        env.define(name.token.clone(), value);
+                    }
                 }
+                step => return Ok(step),
+            }
+        }
+    })
+}
+
+fn evaluate_dotimes(iteration: &Iteration, env: &mut Environment) -> Result<Value, EvalError> {
+    let count = expect_integer(&eval(&iteration.source, env)?, &iteration.token)?;
 
-                let mut body = function.body.clone();
+    in_scope(env, |env| {
+        for index in 0..count {
+            env.define(iteration.name.token.clone(), Value::Integer(index));
+            evaluate_each(&iteration.body, env)?;
+        }
 
-                while let Some(b) = body.pop_front() {
-                    return evaluate_node(b, &mut local_env);
+        Ok(Value::Nil)
+    })
+}
+
+fn evaluate_doseq(iteration: &Iteration, env: &mut Environment) -> Result<Value, EvalError> {
+    let items = expect_list(&eval(&iteration.source, env)?, &iteration.token)?;
+
+    in_scope(env, |env| {
+        for item in items.iter() {
+            env.define(iteration.name.token.clone(), item.clone());
+            evaluate_each(&iteration.body, env)?;
+        }
+
+        Ok(Value::Nil)
+    })
+}
+
+/// Turns an error that unwound to a `catch` into the value it binds.
+fn caught(err: EvalError) -> Value {
+    match err {
+        EvalError::Thrown { value, .. } => *value,
+        native => Value::Error(Rc::new(ErrorValue::from_native(native))),
+    }
+}
+
+/// Evaluates a body down to the value of its last node.
+fn evaluate_sequence(body: &Stack<Node>, env: &mut Environment) -> Result<Value, EvalError> {
+    let step = evaluate_body(body, env)?;
+    resolve(step, env)
+}
+
+/// Runs the body, handing any error to the catch clause. The finally clause runs
+/// on every path, an error raised by it replaces the result. Invoked restarts pass
+/// through to their `restart-case`.
+fn evaluate_try(tr: &Try, env: &mut Environment) -> Result<Value, EvalError> {
+    let (scopes, depth) = (env.scopes.len(), env.depth);
+
+    if tr.catch.is_some() {
+        env.handlers.push(HandlerFrame::Catch);
+    }
+
+    let result = evaluate_sequence(&tr.body, env);
+
+    if tr.catch.is_some() {
+        env.handlers.pop();
+    }
+
+    let result = match (result, &tr.catch) {
+        (Err(EvalError::RestartInvoked { name, id }), _) => {
+            Err(EvalError::RestartInvoked { name, id })
+        }
+        (Err(err), Some(catch)) => {
+            env.scopes.truncate(scopes);
+            env.depth = depth;
+            env.unwinding = false;
+
+            let mut bindings = HashMap::new();
+            bindings.insert(catch.name.token.clone(), caught(err));
+
+            env.push_scope(bindings, false);
+            let result = evaluate_sequence(&catch.body, env);
+            env.pop_scope();
+
+            result
+        }
+        (result, _) => result,
+    };
+
+    if result.is_err() {
+        env.scopes.truncate(scopes);
+        env.depth = depth;
+    }
+
+    let unwinding = std::mem::replace(&mut env.unwinding, false);
+    evaluate_each(&tr.finally, env)?;
+    env.unwinding = unwinding;
+
+    result
+}
+
+fn evaluate_handler_bind(hb: &HandlerBind, env: &mut Environment) -> Result<Value, EvalError> {
+    let mut handlers = Vec::new();
+
+    for pair in hb.handlers.data.iter().collect::<Vec<_>>().chunks(2) {
+        let kind = match eval(pair[0], env)? {
+            Value::Keyword(kind) => kind,
+            other => {
+                return Err(EvalError::TypeError {
+                    token: hb.token.clone(),
+                    expected: "KEYWORD",
+                    found: other.type_name(),
+                })
+            }
+        };
+
+        let function = expect_function(&eval(pair[1], env)?, &hb.token)?;
+
+        handlers.push(Handler {
+            token: hb.token.clone(),
+            kind,
+            function,
+        });
+    }
+
+    env.handlers.push(HandlerFrame::Bind(handlers));
+    let result = evaluate_sequence(&hb.body, env);
+    env.handlers.pop();
+
+    result
+}
+
+/// Evaluates the body with the restarts established. Invoking one of them unwinds
+/// back here and runs its clause with the restart's arguments.
+fn evaluate_restart_case(rc: &RestartCase, env: &mut Environment) -> Result<Value, EvalError> {
+    let (scopes, depth, base) = (env.scopes.len(), env.depth, env.restarts.len());
+
+    let ids = rc
+        .restarts
+        .data
+        .iter()
+        .map(|clause| {
+            let description = match clause.arity.body.first() {
+                Some(Node::Literal(doc))
+                    if doc.token_kind == TokenKind::DocString
+                        && clause.arity.body.data.len() > 1 =>
+                {
+                    doc.token.clone()
                 }
+                _ => clause.name.token.clone(),
+            };
+
+            let takes_value = !clause.arity.parameters.required.is_empty();
+
+            env.establish(&clause.name.token, description, takes_value)
+        })
+        .collect::<Vec<_>>();
+
+    let result = eval(&rc.body, env);
 
-                Ok(EvalResult::Void)
+    env.restarts.truncate(base);
+
+    let clause = match &result {
+        Err(EvalError::RestartInvoked { id, .. }) => ids
+            .iter()
+            .position(|established| established == id)
+            .map(|index| &rc.restarts.data[index]),
+        _ => None,
+    };
+
+    let Some(clause) = clause else {
+        return result;
+    };
+
+    env.scopes.truncate(scopes);
+    env.depth = depth;
+    env.unwinding = false;
+
+    let args = std::mem::take(&mut env.restart_args);
+
+    if unpaired(&clause.arity.parameters, args.len()) {
+        return Err(unpaired_keyword(&clause.name));
+    }
+
+    if !accepts(&clause.arity.parameters, args.len()) {
+        return Err(EvalError::ArityError {
+            token: clause.name.clone(),
+            expected: expected(&clause.arity.parameters),
+            found: args.len(),
+        });
+    }
+
+    in_scope(env, |env| {
+        bind_parameters(&clause.arity.parameters, &clause.name, args, env)?;
+        evaluate_sequence(&clause.arity.body, env)
+    })
+}
+
+/// Evaluates a node that sits in tail position.
+fn eval_tail(node: &Node, env: &mut Environment) -> Result<Step, EvalError> {
+    match node {
+        Node::FunctionCall(fc) => evaluate_function_call(fc, env),
+        Node::Conditional(conditional) => evaluate_conditional(conditional, env),
+        Node::Match(mt) => evaluate_match(mt, env),
+        Node::Logical(logical) => evaluate_logical(logical, env),
+        Node::Loop(lp) => evaluate_loop(lp, env),
+        Node::Recur(recur) => recur
+            .arguments
+            .data
+            .iter()
+            .map(|arg| eval(arg, env))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Step::Recur),
+        _ => eval_value(node, env).map(Step::Value),
+    }
+}
+
+/// Evaluates a single node down to a value.
+///
+/// This is where errors are signalled: the innermost expression an error escapes
+/// from runs the handlers, and is the one replaced by a `use-value` restart.
+pub fn eval(node: &Node, env: &mut Environment) -> Result<Value, EvalError> {
+    loop {
+        let result = eval_tail(node, env).and_then(|step| resolve(step, env));
+
+        match result {
+            Err(err) if !env.unwinding && !matches!(err, EvalError::RestartInvoked { .. }) => {
+                match signal(err, env)? {
+                    Resolution::UseValue(value) => return Ok(value),
+                    Resolution::Retry => continue,
+                }
             }
-            None => return evaluate_math(fc, env),
+            result => return result,
+        }
+    }
+}
+
+/// Runs a pending tail call down to its value.
+fn resolve(step: Step, env: &mut Environment) -> Result<Value, EvalError> {
+    match step {
+        Step::Value(value) => Ok(value),
+        Step::TailCall(function, token, args) => apply(&function, &token, args, env),
+        Step::Recur(_) => unreachable!("recur is only parsed in tail position of a loop"),
+    }
+}
+
+/// Declares the type and its constructor `Name`, predicate `Name?` and one
+/// `Name-field` accessor per field.
+fn evaluate_struct_definition(
+    sd: &StructDefinition,
+    enumeration: Option<&str>,
+    env: &mut Environment,
+) -> Rc<StructType> {
+    let name = env.qualify(&sd.name);
+
+    let kind = Rc::new(StructType {
+        name: name.clone(),
+        fields: sd
+            .fields
+            .data
+            .iter()
+            .map(|field| field.token.clone())
+            .collect(),
+        enumeration: enumeration.map(str::to_string),
+    });
+
+    let operations = [
+        (name.clone(), StructOp::Construct),
+        (format!("{}?", name), StructOp::Predicate),
+    ]
+    .into_iter()
+    .chain(
+        kind.fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (format!("{}-{}", name, field), StructOp::Field(index))),
+    );
+
+    for (function, op) in operations {
+        define_struct_op(function, &kind, op, env);
+    }
+
+    env.types.insert(name, kind.clone());
+
+    kind
+}
+
+fn define_struct_op(name: String, kind: &Rc<StructType>, op: StructOp, env: &mut Environment) {
+    env.functions.insert(
+        name.clone(),
+        Function::Struct {
+            name,
+            kind: kind.clone(),
+            op,
         },
+    );
+}
+
+/// Defines every variant as a struct type, plus `Enum?` recognising all of them.
+fn evaluate_enum_definition(ed: &EnumDefinition, env: &mut Environment) {
+    let name = env.qualify(&ed.name);
+
+    let kinds = ed
+        .variants
+        .data
+        .iter()
+        .map(|variant| evaluate_struct_definition(variant, Some(&name), env))
+        .collect::<Vec<_>>();
+
+    if let Some(kind) = kinds.first() {
+        let predicate = format!("{}?", name);
+        define_struct_op(predicate, kind, StructOp::Member, env);
+    }
+}
+
+/// Declares a multimethod, keeping the methods of an earlier declaration so it can
+/// be reloaded without losing them.
+fn evaluate_multi_definition(md: &MultiDefinition, env: &mut Environment) -> Result<(), EvalError> {
+    let name = env.qualify(&md.name);
+    let dispatch = expect_function(&eval(&md.dispatch, env)?, &md.name)?;
+
+    match env.multimethods.get_mut(&name) {
+        Some(multi) => multi.dispatch = dispatch,
+        None => {
+            env.multimethods.insert(
+                name.clone(),
+                MultiMethod {
+                    dispatch,
+                    methods: Default::default(),
+                },
+            );
+        }
+    }
+
+    env.functions.insert(name.clone(), Function::Multi { name });
+
+    Ok(())
+}
+
+fn evaluate_method_definition(
+    md: &MethodDefinition,
+    env: &mut Environment,
+) -> Result<(), EvalError> {
+    let value = eval(&md.value, env)?;
+
+    let method = Function::Closure(Rc::new(Closure {
+        name: md.name.clone(),
+        arities: md.arities.clone(),
+        captured: env.captured(),
+    }));
+
+    let name = env
+        .global_names(&md.name)?
+        .into_iter()
+        .find(|name| env.multimethods.contains_key(name));
+
+    match name.and_then(|name| env.multimethods.get_mut(&name)) {
+        Some(multi) => {
+            multi.methods.insert(value, method);
+            Ok(())
+        }
+        None => Err(EvalError::ValueError {
+            token: md.name.clone(),
+            message: format!("{} is not declared with defmulti", md.name.token),
+        }),
+    }
+}
+
+/// Evaluates the nodes that never produce a tail call.
+fn eval_value(node: &Node, env: &mut Environment) -> Result<Value, EvalError> {
+    match node {
         Node::FunctionDefinition(fd) => {
-            env.functions.insert(fd.name.token.clone(), fd.clone());
-            Ok(EvalResult::Void)
+            let name = env.qualify(&fd.name);
+            let closure = Closure::from_definition(fd);
+
+            if fd.private {
+                env.private.insert(name.clone());
+            } else {
+                env.private.remove(&name);
+            }
+
+            env.functions
+                .insert(name, Function::Closure(Rc::new(closure)));
+            Ok(Value::Nil)
+        }
+        Node::Namespace(ns) => {
+            module::declare(ns, env);
+            Ok(Value::Nil)
+        }
+        Node::Require(rq) => {
+            module::require(rq, env)?;
+            Ok(Value::Nil)
+        }
+        Node::StructDefinition(sd) => {
+            evaluate_struct_definition(sd, None, env);
+            Ok(Value::Nil)
+        }
+        Node::EnumDefinition(ed) => {
+            evaluate_enum_definition(ed, env);
+            Ok(Value::Nil)
+        }
+        Node::MultiDefinition(md) => {
+            evaluate_multi_definition(md, env)?;
+            Ok(Value::Nil)
+        }
+        Node::MethodDefinition(md) => {
+            evaluate_method_definition(md, env)?;
+            Ok(Value::Nil)
+        }
+        Node::Lambda(lambda) => {
+            let closure = Closure::from_lambda(lambda, env.captured());
+            Ok(Value::Function(Function::Closure(Rc::new(closure))))
         }
         Node::VariableDefinition(vd) => {
-            env.variables.insert(vd.name.token.clone(), vd.clone());
-            Ok(EvalResult::Void)
+            let value = eval(&vd.assignment, env)?;
+            env.assign(&vd.name, value);
+            Ok(Value::Nil)
         }
-        Node::Literal(token) => Ok(EvalResult::Literal(token)),
+        Node::While(wl) => evaluate_while(wl, env),
+        Node::Try(tr) => evaluate_try(tr, env),
+        Node::HandlerBind(hb) => evaluate_handler_bind(hb, env),
+        Node::RestartCase(rc) => evaluate_restart_case(rc, env),
+        Node::DoTimes(iteration) => evaluate_dotimes(iteration, env),
+        Node::DoSeq(iteration) => evaluate_doseq(iteration, env),
+        Node::Literal(token) => parse_literal(token),
 
-        Node::VariableCall(vc) => match env.variables.get(&vc.name.token) {
-            Some(variable) => Ok(EvalResult::Literal(variable.assignment.clone())),
-            None => Err(EvalError::FunctionCallMissing(vc.name)),
+        Node::VariableCall(vc) => match env.lookup(&vc.name)? {
+            Some(variable) => Ok(variable.clone()),
+            None => lookup_function(&vc.name, env).map(Value::Function),
         },
 
-        Node::List(ls) => Ok(EvalResult::List(ls.data)),
+        Node::List(ls) => {
+            let items = ls
+                .data
+                .data
+                .iter()
+                .map(|item| eval(item, env))
+                .collect::<Result<List, _>>()?;
+
+            Ok(Value::List(items))
+        }
+
+        Node::Map(map) => {
+            let mut entries = Map::new();
+
+            for pair in map.data.data.iter().collect::<Vec<_>>().chunks(2) {
+                let key = eval(pair[0], env)?;
+                let value = eval(pair[1], env)?;
+                entries = entries.insert(key, value);
+            }
+
+            Ok(Value::Map(entries))
+        }
+
+        Node::Set(set) => {
+            let members = set
+                .data
+                .data
+                .iter()
+                .map(|member| eval(member, env))
+                .collect::<Result<Set<_>, _>>()?;
+
+            Ok(Value::Set(members))
+        }
 
         _ => unreachable!(),
     }
 }
+
+pub fn evaluate_node(node: Node, env: &mut Environment) -> Result<EvalResult, EvalError> {
+    env.unwinding = false;
+
+    match node {
+        Node::FunctionDefinition(_)
+        | Node::StructDefinition(_)
+        | Node::EnumDefinition(_)
+        | Node::MultiDefinition(_)
+        | Node::MethodDefinition(_)
+        | Node::Namespace(_)
+        | Node::Require(_)
+        | Node::VariableDefinition(_) => {
+            eval(&node, env)?;
+            Ok(EvalResult::Void)
+        }
+        _ => Ok(EvalResult::Value(eval(&node, env)?)),
+    }
+}
@@ -1,54 +1,54 @@
-use crate::{
-    env::Environment,
-    evaluation::{evaluate_node, EvalResult},
-};
+use crate::{error::EvalError, interpreter::Interpreter};
 
-use flop_frontend::{lexer::tokenise, parser::parse};
-use miette::Result;
-use std::{fs::File, io::Read, path::PathBuf};
+use miette::{Report, Result};
+use std::path::{Path, PathBuf};
 
+#[derive(Default)]
 pub struct Program {
-    state: Environment,
+    interpreter: Interpreter,
 }
 
 impl Program {
     pub fn new() -> Self {
         Self {
-            state: Environment::new(),
+            interpreter: Interpreter::new(),
         }
     }
 
-    pub fn run(&mut self, path: &String) -> Result<()> {
-        let mut file = File::open(&path).expect("Error opening file");
-
-        let mut content = String::new();
-
-        let mut namespace = PathBuf::new();
-
-        namespace.push(&path);
-
-        let _ = file
-            .read_to_string(&mut content)
-            .expect("Error reading from file");
+    /// See [`Interpreter::with_max_depth`].
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.interpreter = self.interpreter.with_max_depth(max_depth);
+        self
+    }
 
-        let mut tokens = tokenise(&content, &namespace)?;
+    /// See [`Interpreter::with_stack_size`].
+    pub fn with_stack_size(mut self, stack_size: usize) -> Self {
+        self.interpreter = self.interpreter.with_stack_size(stack_size);
+        self
+    }
 
-        let mut parse = parse(&mut tokens)?;
+    /// See [`Interpreter::with_search_path`].
+    pub fn with_search_path(mut self, search_path: Vec<PathBuf>) -> Self {
+        self.interpreter = self.interpreter.with_search_path(search_path);
+        self
+    }
 
-        while let Some(node) = parse.pop_front() {
-            let eval = evaluate_node(node, &mut self.state)?;
+    /// Evaluates the file at `path`, printing the value of every form that has one.
+    pub fn run(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let result = self
+            .interpreter
+            .eval_file_with(path, |value| println!("{}", value));
 
-            match eval {
-                EvalResult::Void => {}
-                EvalResult::List(stack) => {
-                    println!("{:?}", stack.data)
-                }
-                EvalResult::Literal(n) => {
-                    println!("{:?}", n);
-                }
-            }
-        }
+        report_warnings(&mut self.interpreter);
+        result.map_err(EvalError::into_report)?;
 
         Ok(())
     }
 }
+
+/// Prints the warnings the checker raised since they were last reported.
+pub(crate) fn report_warnings(interpreter: &mut Interpreter) {
+    for warning in interpreter.take_warnings() {
+        eprintln!("{:?}", Report::new(warning));
+    }
+}
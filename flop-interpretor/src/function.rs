@@ -0,0 +1,188 @@
+use crate::{
+    native::Builtin,
+    structure::{StructOp, StructType},
+    value::Value,
+};
+use flop_frontend::{
+    ast::{Arity, FunctionDefinition, Lambda, Parameters},
+    stack::Stack,
+    token::Token,
+};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    rc::Rc,
+};
+
+/// A user defined function together with the variables it closed over.
+#[derive(Debug)]
+pub struct Closure {
+    pub name: Token,
+    pub arities: Stack<Arity>,
+    pub captured: HashMap<String, Value>,
+}
+
+impl Closure {
+    pub fn from_definition(fd: &FunctionDefinition) -> Self {
+        Self {
+            name: fd.name.clone(),
+            arities: fd.arities.clone(),
+            captured: HashMap::new(),
+        }
+    }
+
+    pub fn from_lambda(lambda: &Lambda, captured: HashMap<String, Value>) -> Self {
+        Self {
+            name: lambda.token.clone(),
+            arities: lambda.arities.clone(),
+            captured,
+        }
+    }
+}
+
+impl Closure {
+    /// The first arity whose parameters accept `count` arguments.
+    pub fn select(&self, count: usize) -> Option<&Arity> {
+        self.arities
+            .data
+            .iter()
+            .find(|arity| accepts(&arity.parameters, count))
+    }
+
+    /// Whether `count` arguments would fit an arity but for a keyword argument
+    /// without its value.
+    pub fn unpaired(&self, count: usize) -> bool {
+        self.arities
+            .data
+            .iter()
+            .any(|arity| unpaired(&arity.parameters, count))
+    }
+
+    /// The accepted argument counts, as shown in arity errors.
+    pub fn expected(&self) -> String {
+        self.arities
+            .data
+            .iter()
+            .map(|arity| expected(&arity.parameters))
+            .collect::<Vec<_>>()
+            .join(" or ")
+    }
+}
+
+pub(crate) fn accepts(parameters: &Parameters, count: usize) -> bool {
+    let required = parameters.required.data.len();
+    let positional = required + parameters.optional.data.len();
+
+    if count < required {
+        false
+    } else if !parameters.keywords.is_empty() {
+        count <= positional || (count - positional).is_multiple_of(2)
+    } else {
+        parameters.rest.is_some() || count <= positional
+    }
+}
+
+/// Whether the arguments after the positional ones leave a keyword without its value.
+pub(crate) fn unpaired(parameters: &Parameters, count: usize) -> bool {
+    let positional = parameters.required.data.len() + parameters.optional.data.len();
+
+    !parameters.keywords.is_empty() && count > positional && !accepts(parameters, count)
+}
+
+pub(crate) fn expected(parameters: &Parameters) -> String {
+    let required = parameters.required.data.len();
+    let optional = parameters.optional.data.len();
+
+    if parameters.rest.is_some() || !parameters.keywords.is_empty() {
+        format!("at least {}", required)
+    } else if optional > 0 {
+        format!("{} to {}", required, required + optional)
+    } else {
+        required.to_string()
+    }
+}
+
+/// The dispatch table of a `defmulti`.
+#[derive(Debug, Clone)]
+pub struct MultiMethod {
+    pub dispatch: Function,
+    /// Methods by dispatch value, `:default` runs when no other one matches.
+    pub methods: BTreeMap<Value, Function>,
+}
+
+/// Anything that can sit in the head of a function call.
+#[derive(Clone)]
+pub enum Function {
+    Closure(Rc<Closure>),
+    Builtin {
+        name: String,
+        builtin: Builtin,
+    },
+    /// A constructor, predicate or accessor generated by `defstruct`.
+    Struct {
+        name: String,
+        kind: Rc<StructType>,
+        op: StructOp,
+    },
+    /// A `defmulti`, whose methods live in the environment so later `defmethod`s
+    /// reach every copy of it.
+    Multi {
+        name: String,
+    },
+}
+
+impl Function {
+    pub fn name(&self) -> &str {
+        match self {
+            Function::Closure(closure) => &closure.name.token,
+            Function::Builtin { name, .. }
+            | Function::Struct { name, .. }
+            | Function::Multi { name } => name,
+        }
+    }
+}
+
+/// Closures are equal only to themselves, native functions are equal by name.
+impl Ord for Function {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Function::Closure(a), Function::Closure(b)) => Rc::as_ptr(a).cmp(&Rc::as_ptr(b)),
+            (Function::Closure(_), _) => Ordering::Less,
+            (_, Function::Closure(_)) => Ordering::Greater,
+            (a, b) => a.name().cmp(b.name()),
+        }
+    }
+}
+
+impl PartialOrd for Function {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Function {}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Function::Closure(_) => write!(f, "#<fn {}>", self.name()),
+            Function::Builtin { .. } | Function::Struct { .. } => {
+                write!(f, "#<builtin {}>", self.name())
+            }
+            Function::Multi { .. } => write!(f, "#<multi {}>", self.name()),
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    condition::Debugger,
+    env::Environment,
+    error::{EvalError, SourceError},
+    evaluation::{apply, evaluate_node, lookup_function, EvalResult},
+    module::{self, read_file},
+    native::{FromValue, IntoNative, IntoValue},
+    value::Value,
+};
+use flop_frontend::{
+    ast::Node,
+    error::ParseWarning,
+    lexer::tokenise,
+    parser::parse,
+    stack::Stack,
+    token::{Token, TokenKind},
+};
+use std::path::{Path, PathBuf};
+
+/// Evaluates Flop code on behalf of a Rust program and hands the results back.
+/// Every evaluation shares one environment, so definitions persist between calls.
+///
+/// ```
+/// # use flop_interpretor::{interpreter::Interpreter, value::Value};
+/// let mut interpreter = Interpreter::new();
+///
+/// interpreter.register_fn("twice", |x: i64| 2 * x);
+/// interpreter.eval_str(r#"(defn inc [x] "Adds one" (+ x 1))"#).unwrap();
+///
+/// let value = interpreter.eval_str("(twice (inc 20))").unwrap();
+/// assert_eq!(value.extract::<i64>(), Some(42));
+///
+/// let next: i64 = interpreter.call_as("inc", vec![Value::Integer(1)]).unwrap();
+/// assert_eq!(next, 2);
+///
+/// interpreter.set_global("limit", 10_i64);
+/// assert_eq!(interpreter.get_global("limit"), Some(Value::Integer(10)));
+/// ```
+#[derive(Default)]
+pub struct Interpreter {
+    env: Environment,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            env: Environment::new(),
+        }
+    }
+
+    /// Sets how many non-tail calls may be nested before evaluation fails.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.env.max_depth = max_depth;
+        self
+    }
+
+    /// Sets the size in bytes of the native stack of the thread evaluating, nested
+    /// calls fail with a stack depth error before they run out of it.
+    pub fn with_stack_size(mut self, stack_size: usize) -> Self {
+        self.env.stack_size = stack_size;
+        self
+    }
+
+    /// Adds directories searched for required modules.
+    pub fn with_search_path(mut self, search_path: Vec<PathBuf>) -> Self {
+        self.env.search_path.extend(search_path);
+        self
+    }
+
+    /// Asks `debugger` how to recover from errors no handler takes care of.
+    pub fn with_debugger(mut self, debugger: Debugger) -> Self {
+        self.env.debugger = Some(debugger);
+        self
+    }
+
+    pub fn env(&self) -> &Environment {
+        &self.env
+    }
+
+    pub fn env_mut(&mut self) -> &mut Environment {
+        &mut self.env
+    }
+
+    /// Makes a Rust function callable from Flop, see `Environment::register_fn`.
+    pub fn register_fn<Marker>(
+        &mut self,
+        name: &str,
+        function: impl IntoNative<Marker>,
+    ) -> &mut Self {
+        self.env.register_fn(name, function);
+        self
+    }
+
+    /// Evaluates every form of `code`, returning the value of the last one that has one.
+    pub fn eval_str(&mut self, code: &str) -> Result<Value, EvalError> {
+        evaluate_source(code, Path::new("eval"), &mut self.env, |_| {})
+    }
+
+    /// Evaluates forms parsed ahead of time, like those `flop!` expands to, returning
+    /// the value of the last one that has one.
+    pub fn eval_nodes(&mut self, nodes: Stack<Node>) -> Result<Value, EvalError> {
+        evaluate_nodes(nodes, &mut self.env, |_| {})
+    }
+
+    /// Evaluates every form of the file at `path`, returning the value of the last
+    /// one that has one.
+    pub fn eval_file(&mut self, path: impl AsRef<Path>) -> Result<Value, EvalError> {
+        self.eval_file_with(path, |_| {})
+    }
+
+    /// Like [`Interpreter::eval_file`], handing the value of every form that has
+    /// one to `each` as soon as it is evaluated.
+    pub fn eval_file_with(
+        &mut self,
+        path: impl AsRef<Path>,
+        each: impl FnMut(&Value),
+    ) -> Result<Value, EvalError> {
+        let path = path.as_ref();
+        let content = read_file(path, &host_token(&path.to_string_lossy()))?;
+
+        evaluate_source(&content, path, &mut self.env, each)
+    }
+
+    /// Takes the warnings the checker raised about the code evaluated so far,
+    /// oldest first. The interpreter never prints them itself.
+    pub fn take_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.env.warnings)
+    }
+
+    /// Calls the function `name` refers to at the top level with `args`.
+    pub fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, EvalError> {
+        let token = host_token(name);
+        let function = lookup_function(&token, &self.env)?;
+
+        apply(&function, &token, args, &mut self.env)
+    }
+
+    /// Calls `name` and converts the result, failing with a type error when it
+    /// doesn't fit `T`.
+    pub fn call_as<T: FromValue>(&mut self, name: &str, args: Vec<Value>) -> Result<T, EvalError> {
+        let value = self.call(name, args)?;
+
+        value.extract().ok_or_else(|| EvalError::TypeError {
+            token: host_token(name),
+            expected: T::EXPECTED,
+            found: value.type_name(),
+        })
+    }
+
+    /// The value of the global variable `name`, qualified as `module/name` for
+    /// variables defined inside a module.
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.env.variables.get(name).cloned()
+    }
+
+    /// Binds the global variable `name`, replacing any previous value.
+    pub fn set_global(&mut self, name: &str, value: impl IntoValue) {
+        self.env
+            .variables
+            .insert(name.to_string(), value.into_value());
+    }
+}
+
+/// Stands in for source code in errors about calls made from Rust.
+fn host_token(name: &str) -> Token {
+    Token::new(name, TokenKind::Symbol, 0, 0, name.len(), Path::new("host"))
+}
+
+/// Evaluates every form of `source` as the contents of `path`, handing each value
+/// produced to `each` as soon as it is evaluated. Returns the last such value.
+pub(crate) fn evaluate_source(
+    source: &str,
+    path: &Path,
+    env: &mut Environment,
+    each: impl FnMut(&Value),
+) -> Result<Value, EvalError> {
+    let mut tokens =
+        tokenise(source, path).map_err(|err| EvalError::SourceError(SourceError::new(err)))?;
+
+    let nodes = parse(&mut tokens).map_err(|err| EvalError::SourceError(SourceError::new(err)))?;
+
+    let depth = env.loading.len();
+    let result = evaluate_nodes(nodes, env, each);
+    module::finish(depth, result.is_ok(), env);
+
+    result
+}
+
+/// Evaluates parsed forms in order, handing each value produced to `each`.
+/// Returns the last such value.
+pub(crate) fn evaluate_nodes(
+    mut nodes: Stack<Node>,
+    env: &mut Environment,
+    mut each: impl FnMut(&Value),
+) -> Result<Value, EvalError> {
+    let mut last = Value::Nil;
+
+    while let Some(node) = nodes.pop_front() {
+        let warnings = env.checker.check(&node);
+        env.warnings.extend(warnings);
+
+        if let EvalResult::Value(value) = evaluate_node(node, env)? {
+            each(&value);
+            last = value;
+        }
+    }
+
+    Ok(last)
+}
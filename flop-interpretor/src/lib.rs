@@ -1,6 +1,20 @@
+pub mod condition;
 pub mod env;
 pub mod error;
 pub mod evaluation;
 pub mod file;
+pub mod function;
+pub mod interpreter;
+pub mod list;
+pub mod map;
+pub mod module;
+pub mod native;
 pub mod operation;
+pub mod pattern;
 pub mod repl;
+pub mod stdlib;
+pub mod structure;
+pub mod value;
+
+pub use flop_derive::{flop, flop_export, FlopValue};
+pub use flop_frontend;
@@ -0,0 +1,163 @@
+use crate::value::Value;
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+#[derive(Debug)]
+struct ListNode {
+    value: Value,
+    next: Option<Rc<ListNode>>,
+}
+
+/// A persistent singly linked list.
+///
+/// Cloning, `cons` and `rest` are O(1) because every list shares its tail with
+/// the lists it was built from.
+#[derive(Clone, Default)]
+pub struct List {
+    head: Option<Rc<ListNode>>,
+    len: usize,
+}
+
+impl List {
+    pub fn new() -> Self {
+        Self { head: None, len: 0 }
+    }
+
+    pub fn cons(&self, value: Value) -> Self {
+        Self {
+            head: Some(Rc::new(ListNode {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    pub fn first(&self) -> Option<&Value> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    pub fn rest(&self) -> Self {
+        match &self.head {
+            Some(node) => Self {
+                head: node.next.clone(),
+                len: self.len - 1,
+            },
+            None => Self::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        self.iter()
+            .fold(Self::new(), |acc, value| acc.cons(value.clone()))
+    }
+
+    /// Returns a list of `self` followed by `other`, sharing `other` as the tail.
+    pub fn append(&self, other: &Self) -> Self {
+        self.reverse()
+            .iter()
+            .fold(other.clone(), |acc, value| acc.cons(value.clone()))
+    }
+}
+
+impl Drop for List {
+    // unlink iteratively so long lists don't overflow the stack when dropped
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub struct Iter<'a> {
+    next: Option<&'a ListNode>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+}
+
+impl<'a> IntoIterator for &'a List {
+    type Item = &'a Value;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Value> for List {
+    fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
+        let items: Vec<Value> = iter.into_iter().collect();
+
+        items
+            .into_iter()
+            .rev()
+            .fold(Self::new(), |acc, value| acc.cons(value))
+    }
+}
+
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for List {}
+
+impl Ord for List {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl PartialOrd for List {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for List {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for value in self.iter() {
+            value.hash(state);
+        }
+    }
+}
+
+impl fmt::Debug for List {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
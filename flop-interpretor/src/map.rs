@@ -0,0 +1,334 @@
+use std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    fmt,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+#[derive(Debug)]
+struct TreeNode<K, V> {
+    key: K,
+    value: V,
+    priority: u64,
+    left: Tree<K, V>,
+    right: Tree<K, V>,
+}
+
+type Tree<K, V> = Option<Rc<TreeNode<K, V>>>;
+
+fn priority<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn node<K: Clone, V: Clone>(
+    source: &TreeNode<K, V>,
+    left: Tree<K, V>,
+    right: Tree<K, V>,
+) -> Tree<K, V> {
+    Some(Rc::new(TreeNode {
+        key: source.key.clone(),
+        value: source.value.clone(),
+        priority: source.priority,
+        left,
+        right,
+    }))
+}
+
+/// Splits `tree` into the keys below `key`, the node holding `key` and the keys above it.
+#[allow(clippy::type_complexity)]
+fn split<K: Ord + Clone, V: Clone>(
+    tree: &Tree<K, V>,
+    key: &K,
+) -> (Tree<K, V>, Option<Rc<TreeNode<K, V>>>, Tree<K, V>) {
+    match tree {
+        None => (None, None, None),
+        Some(current) => match key.cmp(&current.key) {
+            Ordering::Less => {
+                let (left, found, right) = split(&current.left, key);
+                (left, found, node(current, right, current.right.clone()))
+            }
+            Ordering::Greater => {
+                let (left, found, right) = split(&current.right, key);
+                (node(current, current.left.clone(), left), found, right)
+            }
+            Ordering::Equal => (
+                current.left.clone(),
+                Some(current.clone()),
+                current.right.clone(),
+            ),
+        },
+    }
+}
+
+/// Joins two trees where every key in `left` sorts before every key in `right`.
+fn merge<K: Clone, V: Clone>(left: &Tree<K, V>, right: &Tree<K, V>) -> Tree<K, V> {
+    match (left, right) {
+        (None, tree) | (tree, None) => tree.clone(),
+        (Some(l), Some(r)) => {
+            if l.priority >= r.priority {
+                node(l, l.left.clone(), merge(&l.right, right))
+            } else {
+                node(r, merge(left, &r.left), r.right.clone())
+            }
+        }
+    }
+}
+
+/// A persistent ordered map.
+///
+/// Updates copy only the path to the changed key, every other node is shared
+/// with the map it was derived from.
+pub struct Map<K, V> {
+    root: Tree<K, V>,
+    len: usize,
+}
+
+impl<K, V> Clone for Map<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Self { root: None, len: 0 }
+    }
+}
+
+impl<K: Ord + Hash + Clone, V: Clone> Map<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut current = self.root.as_deref();
+
+        while let Some(tree_node) = current {
+            current = match key.cmp(&tree_node.key) {
+                Ordering::Less => tree_node.left.as_deref(),
+                Ordering::Greater => tree_node.right.as_deref(),
+                Ordering::Equal => return Some(&tree_node.value),
+            };
+        }
+
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let (left, found, right) = split(&self.root, &key);
+
+        let single = Some(Rc::new(TreeNode {
+            priority: priority(&key),
+            key,
+            value,
+            left: None,
+            right: None,
+        }));
+
+        Self {
+            root: merge(&merge(&left, &single), &right),
+            len: if found.is_some() {
+                self.len
+            } else {
+                self.len + 1
+            },
+        }
+    }
+
+    pub fn remove(&self, key: &K) -> Self {
+        match split(&self.root, key) {
+            (left, Some(_), right) => Self {
+                root: merge(&left, &right),
+                len: self.len - 1,
+            },
+            (_, None, _) => self.clone(),
+        }
+    }
+}
+
+impl<K, V> Map<K, V> {
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a TreeNode<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut current: Option<&'a TreeNode<K, V>>) {
+        while let Some(tree_node) = current {
+            self.stack.push(tree_node);
+            current = tree_node.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree_node = self.stack.pop()?;
+        self.push_left(tree_node.right.as_deref());
+        Some((&tree_node.key, &tree_node.value))
+    }
+}
+
+impl<K: Ord + Hash + Clone, V: Clone> FromIterator<(K, V)> for Map<K, V> {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(Self::new(), |map, (key, value)| map.insert(key, value))
+    }
+}
+
+impl<K: Ord, V: Ord> Ord for Map<K, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<K: Ord, V: Ord> PartialOrd for Map<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V: Ord> PartialEq for Map<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, V: Ord> Eq for Map<K, V> {}
+
+impl<K: Hash, V: Hash> Hash for Map<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for entry in self.iter() {
+            entry.hash(state);
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Map<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// A persistent ordered set, backed by a [`Map`] with unit values.
+pub struct Set<K> {
+    map: Map<K, ()>,
+}
+
+impl<K> Clone for Set<K> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+}
+
+impl<K> Default for Set<K> {
+    fn default() -> Self {
+        Self {
+            map: Map::default(),
+        }
+    }
+}
+
+impl<K: Ord> Ord for Set<K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.map.cmp(&other.map)
+    }
+}
+
+impl<K: Ord> PartialOrd for Set<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord> PartialEq for Set<K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<K: Ord> Eq for Set<K> {}
+
+impl<K: Hash> Hash for Set<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.map.hash(state);
+    }
+}
+
+impl<K: Ord + Hash + Clone> Set<K> {
+    pub fn new() -> Self {
+        Self { map: Map::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn insert(&self, key: K) -> Self {
+        Self {
+            map: self.map.insert(key, ()),
+        }
+    }
+
+    pub fn remove(&self, key: &K) -> Self {
+        Self {
+            map: self.map.remove(key),
+        }
+    }
+}
+
+impl<K> Set<K> {
+    pub fn iter(&self) -> impl Iterator<Item = &K> {
+        self.map.iter().map(|(key, _)| key)
+    }
+}
+
+impl<K: Ord + Hash + Clone> FromIterator<K> for Set<K> {
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(Self::new(), |set, key| set.insert(key))
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for Set<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{env::Environment, error::EvalError, interpreter::evaluate_source, value::Value};
+use flop_frontend::{
+    ast::{Namespace, Require},
+    token::Token,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+fn module_error(token: &Token, message: String) -> EvalError {
+    EvalError::ModuleError {
+        token: token.clone(),
+        message,
+    }
+}
+
+/// Makes the file of `ns` the module it names. A file run directly rather than
+/// required stays in `loading` while it runs, so requiring it back is a cycle,
+/// until [`finish`] records it as loaded.
+pub fn declare(ns: &Namespace, env: &mut Environment) {
+    let name = ns.name.token.clone();
+
+    env.modules
+        .entry(ns.token.namespace.clone())
+        .or_default()
+        .name = Some(name.clone());
+
+    if !env.loaded.contains(&name) && !env.loading.contains(&name) {
+        env.loading.push(name);
+    }
+}
+
+/// Ends the modules a file declared while it ran, `loading` held `depth` names
+/// before. A file that ran to the end is loaded, requiring it later only adds
+/// the alias.
+pub(crate) fn finish(depth: usize, succeeded: bool, env: &mut Environment) {
+    let declared = env.loading.split_off(depth.min(env.loading.len()));
+
+    if succeeded {
+        env.loaded.extend(declared);
+    }
+}
+
+/// Loads a module the first time it is required, then records the alias in the
+/// requiring file.
+pub fn require(rq: &Require, env: &mut Environment) -> Result<(), EvalError> {
+    let name = rq.module.token.clone();
+
+    if !env.loaded.contains(&name) {
+        if let Some(start) = env.loading.iter().position(|loading| *loading == name) {
+            let cycle = env.loading[start..]
+                .iter()
+                .chain(std::iter::once(&name))
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            return Err(module_error(
+                &rq.module,
+                format!("circular require: {}", cycle),
+            ));
+        }
+
+        let path = find_module(rq, env)?;
+
+        env.loading.push(name.clone());
+        let result = load_file(&path, &rq.module, env);
+        env.loading.pop();
+
+        result?;
+
+        if env
+            .modules
+            .get(&path)
+            .and_then(|module| module.name.as_ref())
+            != Some(&name)
+        {
+            return Err(module_error(
+                &rq.module,
+                format!("{} does not declare (ns {})", path.display(), name),
+            ));
+        }
+
+        env.loaded.insert(name.clone());
+    }
+
+    if let Some(alias) = &rq.alias {
+        env.modules
+            .entry(rq.token.namespace.clone())
+            .or_default()
+            .aliases
+            .insert(alias.token.clone(), name);
+    }
+
+    Ok(())
+}
+
+/// Finds the file of a required module, `a.b` being `a/b.ls`. It is looked for
+/// next to the requiring file, then from the root directory of the requiring
+/// module, then in every directory of the search path.
+fn find_module(rq: &Require, env: &Environment) -> Result<PathBuf, EvalError> {
+    let relative = PathBuf::from(format!("{}.ls", rq.module.token.replace('.', "/")));
+
+    let directory = rq
+        .token
+        .namespace
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    // `a.b` declared in `root/a/b.ls` has its root two levels up from the file
+    let root = env.module_name(&rq.token).and_then(|own| {
+        directory
+            .ancestors()
+            .nth(own.split('.').count() - 1)
+            .map(Path::to_path_buf)
+    });
+
+    let directories = std::iter::once(directory)
+        .chain(root)
+        .chain(env.search_path.iter().cloned())
+        .collect::<Vec<_>>();
+
+    directories
+        .iter()
+        .map(|directory| directory.join(&relative))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            let searched = directories
+                .iter()
+                .map(|directory| directory.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            module_error(
+                &rq.module,
+                format!("cannot find {} in [{}]", relative.display(), searched),
+            )
+        })
+}
+
+/// Evaluates every form of the file at `path`, reporting errors against that file.
+/// Returns the value of the last form that has one.
+pub fn load_file(path: &Path, token: &Token, env: &mut Environment) -> Result<Value, EvalError> {
+    let content = read_file(path, token)?;
+
+    evaluate_source(&content, path, env, |_| {})
+}
+
+/// The contents of the file at `path`, failing with a module error at `token`.
+pub(crate) fn read_file(path: &Path, token: &Token) -> Result<String, EvalError> {
+    fs::read_to_string(path)
+        .map_err(|err| module_error(token, format!("cannot read {}: {}", path.display(), err)))
+}
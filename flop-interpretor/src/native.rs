@@ -0,0 +1,309 @@
+use crate::{
+    env::Environment, error::EvalError, function::Function, list::List, stdlib::expect_arity,
+    value::Value,
+};
+use flop_frontend::token::Token;
+use std::{fmt::Display, marker::PhantomData, rc::Rc};
+
+/// A function implemented in Rust, called with the environment, the token of the
+/// call and the evaluated arguments.
+pub type Builtin = Rc<dyn Fn(&mut Environment, &Token, Vec<Value>) -> Result<Value, EvalError>>;
+
+/// Rust values a Flop argument can be converted into.
+pub trait FromValue: Sized {
+    /// Name of the accepted type in type errors.
+    const EXPECTED: &'static str;
+
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+/// Rust values a native function can hand back to Flop.
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+/// An error returned by a native function, reported where Flop called it. Any
+/// displayable error converts into it, so natives can use `?` on their own errors.
+#[derive(Debug, Clone)]
+pub struct NativeError {
+    pub message: String,
+}
+
+impl<E: Display> From<E> for NativeError {
+    fn from(error: E) -> Self {
+        Self {
+            message: error.to_string(),
+        }
+    }
+}
+
+impl NativeError {
+    /// The error reported for a call at `token`.
+    pub fn at(self, token: &Token) -> EvalError {
+        EvalError::NativeError {
+            token: token.clone(),
+            message: self.message,
+        }
+    }
+}
+
+/// What a native function may return: a value, or a `Result` of one.
+pub trait IntoResult {
+    fn into_result(self) -> Result<Value, NativeError>;
+}
+
+impl<T: IntoValue> IntoResult for T {
+    fn into_result(self) -> Result<Value, NativeError> {
+        Ok(self.into_value())
+    }
+}
+
+impl<T: IntoValue, E: Into<NativeError>> IntoResult for Result<T, E> {
+    fn into_result(self) -> Result<Value, NativeError> {
+        self.map(IntoValue::into_value).map_err(Into::into)
+    }
+}
+
+/// Rust functions that can be registered with `Environment::register_fn`. The
+/// marker tells the accepted shapes apart.
+pub trait IntoNative<Marker> {
+    fn into_native(self) -> Builtin;
+}
+
+/// Functions with the builtin signature, given the environment, the call token
+/// and the arguments as they are.
+pub struct Raw;
+
+/// Functions taking any number of arguments as a slice.
+pub struct Variadic<R>(PhantomData<R>);
+
+/// Functions with a fixed number of typed parameters.
+pub struct Typed<Args, R>(PhantomData<(Args, R)>);
+
+impl<F> IntoNative<Raw> for F
+where
+    F: Fn(&mut Environment, &Token, Vec<Value>) -> Result<Value, EvalError> + 'static,
+{
+    fn into_native(self) -> Builtin {
+        Rc::new(self)
+    }
+}
+
+impl<F, R> IntoNative<Variadic<R>> for F
+where
+    F: Fn(&[Value]) -> R + 'static,
+    R: IntoResult,
+{
+    fn into_native(self) -> Builtin {
+        Rc::new(move |_env, token, args| self(&args).into_result().map_err(|err| err.at(token)))
+    }
+}
+
+/// Converts an argument, failing with a type error at `token`.
+pub fn convert<T: FromValue>(value: &Value, token: &Token) -> Result<T, EvalError> {
+    T::from_value(value).ok_or_else(|| EvalError::TypeError {
+        token: token.clone(),
+        expected: T::EXPECTED,
+        found: value.type_name(),
+    })
+}
+
+macro_rules! typed {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg),*> IntoNative<Typed<($($arg,)*), R>> for F
+        where
+            F: Fn($($arg),*) -> R + 'static,
+            R: IntoResult,
+            $($arg: FromValue,)*
+        {
+            #[allow(non_snake_case, unused_variables, unused_mut)]
+            fn into_native(self) -> Builtin {
+                Rc::new(move |_env, token, args| {
+                    expect_arity(token, &args, 0 $(+ { stringify!($arg); 1 })*)?;
+
+                    let mut args = args.iter();
+                    $(let $arg = convert::<$arg>(args.next().unwrap_or(&Value::Nil), token)?;)*
+
+                    self($($arg),*).into_result().map_err(|err| err.at(token))
+                })
+            }
+        }
+    };
+}
+
+typed!();
+typed!(A);
+typed!(A, B);
+typed!(A, B, C);
+typed!(A, B, C, D);
+
+impl FromValue for Value {
+    const EXPECTED: &'static str = "ANY";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl FromValue for i64 {
+    const EXPECTED: &'static str = "INTEGER";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+/// Integers are widened, as in arithmetic.
+impl FromValue for f64 {
+    const EXPECTED: &'static str = "NUMBER";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Integer(n) => Some(*n as f64),
+            Value::Float(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for bool {
+    const EXPECTED: &'static str = "BOOLEAN";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for String {
+    const EXPECTED: &'static str = "STRING";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Function {
+    const EXPECTED: &'static str = "FUNCTION";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Function(function) => Some(function.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// `nil` is treated as the empty list.
+impl FromValue for List {
+    const EXPECTED: &'static str = "LIST";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::List(list) => Some(list.clone()),
+            Value::Nil => Some(List::new()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    const EXPECTED: &'static str = "LIST";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        List::from_value(value)?.iter().map(T::from_value).collect()
+    }
+}
+
+/// `nil` converts to `None`.
+impl<T: FromValue> FromValue for Option<T> {
+    const EXPECTED: &'static str = T::EXPECTED;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Nil => Some(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Nil
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> Value {
+        Value::Integer(self)
+    }
+}
+
+impl IntoValue for usize {
+    fn into_value(self) -> Value {
+        Value::Integer(self as i64)
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Float(self)
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        Value::Bool(self)
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl IntoValue for Function {
+    fn into_value(self) -> Value {
+        Value::Function(self)
+    }
+}
+
+impl IntoValue for List {
+    fn into_value(self) -> Value {
+        Value::List(self)
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::List(self.into_iter().map(IntoValue::into_value).collect())
+    }
+}
+
+/// `None` converts to `nil`.
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        self.map_or(Value::Nil, IntoValue::into_value)
+    }
+}
@@ -1,52 +1,125 @@
-use flop_frontend::token::Token;
+use std::cmp::Ordering;
 
-use crate::error::EvalError;
+/// The numeric tower: integer arithmetic is exact and checked, mixing in a float
+/// promotes the whole operation to floating point.
+#[derive(Debug, Clone, Copy)]
+pub enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Number {
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::Integer(n) => n as f64,
+            Number::Float(n) => n,
+        }
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.as_f64() == 0.0
+    }
+
+    pub fn compare(self, other: Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => Some(a.cmp(&b)),
+            (a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+        }
+    }
+}
 
 pub enum Operation {
     Add,
     Subtract,
     Divide,
     Multiply,
-    Equal,
-    GreaterThan,
-    GreaterThanOrEqual,
-    LessThan,
-    LessThanOrEqual,
+    Quotient,
+    Remainder,
+    Modulo,
+    Power,
 }
 
 impl Operation {
-    pub fn apply(&self, a: i64, b: i64) -> i64 {
+    pub fn apply(&self, a: Number, b: Number) -> Result<Number, &'static str> {
+        let divides = matches!(
+            self,
+            Operation::Divide | Operation::Quotient | Operation::Remainder | Operation::Modulo
+        );
+
+        if divides && b.is_zero() {
+            return Err("division by zero");
+        }
+
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => self.apply_integer(a, b),
+            (a, b) => Ok(Number::Float(self.apply_float(a.as_f64(), b.as_f64()))),
+        }
+    }
+
+    fn apply_integer(&self, a: i64, b: i64) -> Result<Number, &'static str> {
+        let result = match self {
+            Operation::Add => a.checked_add(b),
+            Operation::Subtract => a.checked_sub(b),
+            Operation::Multiply => a.checked_mul(b),
+            Operation::Divide if a.checked_rem(b).is_some_and(|r| r != 0) => {
+                return Ok(Number::Float(a as f64 / b as f64));
+            }
+            Operation::Divide | Operation::Quotient => a.checked_div(b),
+            Operation::Remainder => a.checked_rem(b),
+            Operation::Modulo => a.checked_rem(b).map(|r| {
+                if r != 0 && (r < 0) != (b < 0) {
+                    r + b
+                } else {
+                    r
+                }
+            }),
+            Operation::Power => match u32::try_from(b) {
+                Ok(exponent) => a.checked_pow(exponent),
+                Err(_) => return Ok(Number::Float((a as f64).powf(b as f64))),
+            },
+        };
+
+        result.map(Number::Integer).ok_or("integer overflow")
+    }
+
+    fn apply_float(&self, a: f64, b: f64) -> f64 {
         match self {
             Operation::Add => a + b,
             Operation::Subtract => a - b,
-            Operation::Divide => a / b,
             Operation::Multiply => a * b,
-            Operation::Equal => (a == b) as i64,
-            Operation::GreaterThan => (a > b) as i64,
-            Operation::GreaterThanOrEqual => (a >= b) as i64,
-            Operation::LessThan => (a < b) as i64,
-            Operation::LessThanOrEqual => (a <= b) as i64,
+            Operation::Divide => a / b,
+            Operation::Quotient => (a / b).trunc(),
+            Operation::Remainder => a % b,
+            Operation::Modulo => a - b * (a / b).floor(),
+            Operation::Power => a.powf(b),
         }
     }
 }
 
-impl TryFrom<&Token> for Operation {
-    type Error = EvalError;
-
-    fn try_from(token: &Token) -> Result<Self, Self::Error> {
-        let value: &str = &token.token;
-
-        match value {
-            "+" => Ok(Operation::Add),
-            "-" => Ok(Operation::Subtract),
-            "/" => Ok(Operation::Divide),
-            "*" => Ok(Operation::Multiply),
-            "==" => Ok(Operation::Equal),
-            ">=" => Ok(Operation::GreaterThanOrEqual),
-            "<=" => Ok(Operation::LessThanOrEqual),
-            ">" => Ok(Operation::GreaterThan),
-            "<" => Ok(Operation::LessThan),
-            _ => Err(EvalError::SymbolError(token.clone())),
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+}
+
+impl Comparison {
+    pub fn test(&self, a: Number, b: Number) -> bool {
+        let ordering = a.compare(b);
+
+        match self {
+            Comparison::Equal => ordering == Some(Ordering::Equal),
+            Comparison::NotEqual => ordering != Some(Ordering::Equal),
+            Comparison::GreaterThan => ordering == Some(Ordering::Greater),
+            Comparison::GreaterThanOrEqual => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+            Comparison::LessThan => ordering == Some(Ordering::Less),
+            Comparison::LessThanOrEqual => {
+                matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+            }
         }
     }
 }
@@ -0,0 +1,89 @@
+use crate::{
+    env::Environment, error::EvalError, evaluation::parse_literal, list::List, value::Value,
+};
+use flop_frontend::ast::Pattern;
+use std::collections::HashMap;
+
+/// Tests `value` against `pattern`, adding the names it binds to `bindings`.
+pub fn match_pattern(
+    pattern: &Pattern,
+    value: &Value,
+    bindings: &mut HashMap<String, Value>,
+    env: &Environment,
+) -> Result<bool, EvalError> {
+    match pattern {
+        Pattern::Wildcard(_) => Ok(true),
+        Pattern::Literal(token) => Ok(parse_literal(token)? == *value),
+        Pattern::Bind(name) => {
+            bindings.insert(name.token.clone(), value.clone());
+            Ok(true)
+        }
+        Pattern::List { items, rest } => {
+            let Value::List(list) = value else {
+                return Ok(false);
+            };
+
+            match_list(items.data.iter(), rest.as_deref(), list, bindings, env)
+        }
+        Pattern::Constructor { name, fields, rest } => {
+            match (env.resolve(&env.types, name)?, value) {
+                (None, _) => {
+                    let Value::List(list) = value else {
+                        return Ok(false);
+                    };
+
+                    let head = Pattern::Bind(name.clone());
+                    let items = std::iter::once(&head).chain(fields.data.iter());
+
+                    match_list(items, rest.as_deref(), list, bindings, env)
+                }
+                // the fields are matched like a list of the values, `& rest`
+                // taking those after the named ones
+                (Some(kind), Value::Struct(instance)) if instance.kind.is(kind) => {
+                    let values = instance.values.iter().cloned().collect::<List>();
+
+                    match_list(fields.data.iter(), rest.as_deref(), &values, bindings, env)
+                }
+                _ => Ok(false),
+            }
+        }
+        Pattern::Map { entries } => {
+            let Value::Map(map) = value else {
+                return Ok(false);
+            };
+
+            for (key, pattern) in entries.data.iter() {
+                match map.get(&parse_literal(key)?) {
+                    Some(entry) if match_pattern(pattern, entry, bindings, env)? => {}
+                    _ => return Ok(false),
+                }
+            }
+
+            Ok(true)
+        }
+    }
+}
+
+fn match_list<'a>(
+    items: impl Iterator<Item = &'a Pattern>,
+    rest: Option<&Pattern>,
+    list: &List,
+    bindings: &mut HashMap<String, Value>,
+    env: &Environment,
+) -> Result<bool, EvalError> {
+    let mut remaining = list.clone();
+
+    for item in items {
+        match remaining.first() {
+            Some(first) if match_pattern(item, first, bindings, env)? => {
+                remaining = remaining.rest()
+            }
+            _ => return Ok(false),
+        }
+    }
+
+    match rest {
+        Some(rest) => match_pattern(rest, &Value::List(remaining), bindings, env),
+        None => Ok(remaining.is_empty()),
+    }
+}
@@ -1,69 +1,148 @@
 use crate::{
+    condition::Restart,
     env::Environment,
-    evaluation::{evaluate_node, EvalResult},
+    error::EvalError,
+    file::report_warnings,
+    interpreter::{evaluate_source, Interpreter},
+    value::Value,
 };
-use flop_frontend::{lexer::tokenise, parser::parse};
 use miette::Result;
 use std::{
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
+#[derive(Default)]
 pub struct Repl {
-    state: Environment,
+    interpreter: Interpreter,
 }
 
 impl Repl {
     pub fn new() -> Self {
         Self {
-            state: Environment::new(),
+            interpreter: Interpreter::new().with_debugger(debugger),
         }
     }
 
+    /// See [`Interpreter::with_max_depth`].
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.interpreter = self.interpreter.with_max_depth(max_depth);
+        self
+    }
+
+    /// See [`Interpreter::with_stack_size`].
+    pub fn with_stack_size(mut self, stack_size: usize) -> Self {
+        self.interpreter = self.interpreter.with_stack_size(stack_size);
+        self
+    }
+
+    /// See [`Interpreter::with_search_path`].
+    pub fn with_search_path(mut self, search_path: Vec<PathBuf>) -> Self {
+        self.interpreter = self.interpreter.with_search_path(search_path);
+        self
+    }
+
     pub fn run(&mut self) -> Result<()> {
         println!("Starting REPL mode...");
 
-        loop {
-            print!("> ");
+        while let Some(input) = read_line("> ") {
+            if input.trim() == "exit" || input.trim() == "quit" {
+                break;
+            }
 
-            let mut input = String::new();
+            let result = evaluate_input(&input, self.interpreter.env_mut());
+            report_warnings(&mut self.interpreter);
 
-            if let Err(e) = io::stdout().flush() {
-                println!("Error flushing stdout: {:?}", e);
+            match result {
+                Ok(values) => {
+                    for value in values {
+                        println!("{}", value);
+                    }
+                }
+                Err(err) => eprintln!("{:?}", err.into_report()),
             }
+        }
 
-            if let Err(e) = io::stdin().read_line(&mut input) {
-                println!("Error reading line: {:?}", e);
-            }
+        Ok(())
+    }
+}
 
-            if input.trim() == "exit" || input.trim() == "quit" {
-                break;
-            }
+/// Prints `prompt` and reads a line, `None` once stdin is closed.
+fn read_line(prompt: &str) -> Option<String> {
+    print!("{}", prompt);
 
-            // temp namespace
-            let mut namespace = PathBuf::new();
+    if let Err(e) = io::stdout().flush() {
+        println!("Error flushing stdout: {:?}", e);
+    }
 
-            namespace.push("repl");
+    let mut input = String::new();
 
-            let mut tokens = tokenise(&input, &namespace)?;
+    match io::stdin().read_line(&mut input) {
+        Ok(0) => None,
+        Ok(_) => Some(input),
+        Err(e) => {
+            println!("Error reading line: {:?}", e);
+            None
+        }
+    }
+}
 
-            let mut parse = parse(&mut tokens)?;
+/// Evaluates every form of `input`, returning the values of those that produce one.
+fn evaluate_input(input: &str, env: &mut Environment) -> Result<Vec<Value>, EvalError> {
+    let mut values = Vec::new();
 
-            while let Some(node) = parse.pop_front() {
-                let eval = evaluate_node(node, &mut self.state)?;
+    evaluate_source(input, Path::new("repl"), env, |value| {
+        values.push(value.clone())
+    })?;
 
-                match eval {
-                    EvalResult::Void => {}
-                    EvalResult::List(n) => {
-                        println!("{:?}", n.data);
-                    }
-                    EvalResult::Literal(n) => {
-                        println!("{:?}", n);
-                    }
-                }
+    Ok(values)
+}
+
+/// Offers the active restarts, innermost first, for an error no handler took care
+/// of. Restarts taking a value read it as an expression, or a definition for
+/// `define`.
+fn debugger(
+    env: &mut Environment,
+    err: &EvalError,
+    restarts: &[Restart],
+) -> Option<(usize, Vec<Value>)> {
+    let choices = restarts.iter().rev().collect::<Vec<_>>();
+
+    println!("error: {}", err);
+    println!("Available restarts:");
+
+    for (index, restart) in choices.iter().enumerate() {
+        println!("  {}: [{}] {}", index, restart.name, restart.description);
+    }
+
+    println!("  {}: [abort] Return to the top level", choices.len());
+
+    loop {
+        let input = read_line("restart> ")?;
+
+        let restart = match input.trim().parse::<usize>() {
+            Ok(index) if index < choices.len() => choices[index],
+            Ok(index) if index == choices.len() => return None,
+            _ => {
+                println!("Enter a number between 0 and {}", choices.len());
+                continue;
             }
+        };
+
+        if !restart.takes_value {
+            return Some((restart.id, Vec::new()));
         }
 
-        Ok(())
+        let input = read_line("value> ")?;
+
+        // errors in the value are reported, not debugged
+        let debugger = env.debugger.take();
+        let values = evaluate_input(&input, env);
+        env.debugger = debugger;
+
+        match values {
+            Ok(values) => return Some((restart.id, values)),
+            Err(err) => eprintln!("{:?}", err.into_report()),
+        }
     }
 }
@@ -0,0 +1,280 @@
+use super::{expect_arity, expect_list, string};
+use crate::{env::Environment, error::EvalError, map::Map, structure::StructValue, value::Value};
+use flop_frontend::{ast::CASE_EQUAL, token::Token};
+use std::rc::Rc;
+
+pub fn register(env: &mut Environment) {
+    env.register_fn("hash-map", hash_map);
+    env.register_fn("hash-set", hash_set);
+    env.register_fn("get", get);
+    env.register_fn("assoc", assoc);
+    env.register_fn("dissoc", dissoc);
+    env.register_fn("keys", keys);
+    env.register_fn("vals", vals);
+    env.register_fn("contains?", contains);
+    env.register_fn("conj", conj);
+    env.register_fn("merge", merge);
+    env.register_fn("=", equal);
+    env.register_fn(CASE_EQUAL, equal);
+}
+
+/// `nil` is treated as the empty map.
+fn expect_map(value: &Value, token: &Token) -> Result<Map<Value, Value>, EvalError> {
+    match value {
+        Value::Map(map) => Ok(map.clone()),
+        Value::Nil => Ok(Map::new()),
+        other => Err(EvalError::TypeError {
+            token: token.clone(),
+            expected: "MAP",
+            found: other.type_name(),
+        }),
+    }
+}
+
+fn expect_pairs(token: &Token, args: &[Value]) -> Result<(), EvalError> {
+    if args.len().is_multiple_of(2) {
+        Ok(())
+    } else {
+        Err(EvalError::ArityError {
+            token: token.clone(),
+            expected: "key value pairs".to_string(),
+            found: args.len(),
+        })
+    }
+}
+
+fn hash_map(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_pairs(token, &args)?;
+
+    let map = args
+        .chunks(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect();
+
+    Ok(Value::Map(map))
+}
+
+fn hash_set(_env: &mut Environment, _token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    Ok(Value::Set(args.into_iter().collect()))
+}
+
+/// `(get coll key default?)` looks up map keys, set members and list indices.
+fn get(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let (coll, key, default) = match args.as_slice() {
+        [coll, key] => (coll, key, Value::Nil),
+        [coll, key, default] => (coll, key, default.clone()),
+        _ => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "2 or 3".to_string(),
+                found: args.len(),
+            })
+        }
+    };
+
+    let found = match (coll, key) {
+        (Value::Map(map), key) => map.get(key).cloned(),
+        (Value::Set(set), key) => set.contains(key).then(|| key.clone()),
+        (Value::Struct(instance), Value::Keyword(field)) => instance.get(field).cloned(),
+        (Value::List(list), Value::Integer(index)) => usize::try_from(*index)
+            .ok()
+            .and_then(|i| list.iter().nth(i))
+            .cloned(),
+        (Value::Nil, _) | (Value::List(_), _) | (Value::Struct(_), _) => None,
+        (other, _) => {
+            return Err(EvalError::TypeError {
+                token: token.clone(),
+                expected: "MAP",
+                found: other.type_name(),
+            })
+        }
+    };
+
+    Ok(found.unwrap_or(default))
+}
+
+/// Functional update of struct fields, `(assoc point :x 1)`.
+fn assoc_fields(
+    instance: &StructValue,
+    token: &Token,
+    entries: &[Value],
+) -> Result<Value, EvalError> {
+    expect_pairs(token, entries)?;
+
+    let mut updated = instance.clone();
+
+    for pair in entries.chunks(2) {
+        let field = match &pair[0] {
+            Value::Keyword(field) => field,
+            other => {
+                return Err(EvalError::TypeError {
+                    token: token.clone(),
+                    expected: "KEYWORD",
+                    found: other.type_name(),
+                })
+            }
+        };
+
+        updated = updated
+            .with(field, pair[1].clone())
+            .ok_or_else(|| EvalError::ValueError {
+                token: token.clone(),
+                message: format!("{} has no field :{}", instance.kind.name, field),
+            })?;
+    }
+
+    Ok(Value::Struct(Rc::new(updated)))
+}
+
+fn assoc(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    if let Some((Value::Struct(instance), entries)) = args.split_first() {
+        return assoc_fields(instance, token, entries);
+    }
+
+    let (map, entries) = match args.split_first() {
+        Some((map, entries)) if !entries.is_empty() => (expect_map(map, token)?, entries),
+        _ => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "at least 3".to_string(),
+                found: args.len(),
+            })
+        }
+    };
+
+    expect_pairs(token, entries)?;
+
+    let map = entries.chunks(2).fold(map, |map, pair| {
+        map.insert(pair[0].clone(), pair[1].clone())
+    });
+
+    Ok(Value::Map(map))
+}
+
+/// `(dissoc coll key ...)` removes keys from a map or members from a set.
+fn dissoc(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    match args.split_first() {
+        Some((Value::Set(set), members)) => Ok(Value::Set(
+            members.iter().fold(set.clone(), |set, key| set.remove(key)),
+        )),
+        Some((map, keys)) => {
+            let map = expect_map(map, token)?;
+            Ok(Value::Map(
+                keys.iter().fold(map, |map, key| map.remove(key)),
+            ))
+        }
+        None => Err(EvalError::ArityError {
+            token: token.clone(),
+            expected: "at least 1".to_string(),
+            found: 0,
+        }),
+    }
+}
+
+fn keys(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let map = expect_map(&args[0], token)?;
+
+    Ok(Value::List(
+        map.iter().map(|(key, _)| key.clone()).collect(),
+    ))
+}
+
+fn vals(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let map = expect_map(&args[0], token)?;
+
+    Ok(Value::List(
+        map.iter().map(|(_, value)| value.clone()).collect(),
+    ))
+}
+
+/// Checks map keys, set members, list elements or, for strings, substrings.
+fn contains(env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let found = match &args[0] {
+        Value::String(_) => return string::contains(env, token, args),
+        Value::Map(map) => map.contains_key(&args[1]),
+        Value::Set(set) => set.contains(&args[1]),
+        Value::List(list) => list.iter().any(|item| *item == args[1]),
+        Value::Nil => false,
+        other => {
+            return Err(EvalError::TypeError {
+                token: token.clone(),
+                expected: "MAP",
+                found: other.type_name(),
+            })
+        }
+    };
+
+    Ok(Value::Bool(found))
+}
+
+/// `(conj coll x ...)` adds to the front of a list, into a set, or `[key value]` into a map.
+fn conj(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let (coll, items) = args.split_first().ok_or(EvalError::ArityError {
+        token: token.clone(),
+        expected: "at least 1".to_string(),
+        found: 0,
+    })?;
+
+    match coll {
+        Value::Set(set) => Ok(Value::Set(
+            items
+                .iter()
+                .fold(set.clone(), |set, item| set.insert(item.clone())),
+        )),
+        Value::Map(map) => {
+            let mut map = map.clone();
+
+            for item in items {
+                let entry = expect_list(item, token)?;
+
+                match (entry.len(), entry.first()) {
+                    (2, Some(key)) => {
+                        let value = entry.rest().first().cloned().unwrap_or(Value::Nil);
+                        map = map.insert(key.clone(), value);
+                    }
+                    _ => {
+                        return Err(EvalError::ValueError {
+                            token: token.clone(),
+                            message: "conj on a map expects [key value] entries".to_string(),
+                        })
+                    }
+                }
+            }
+
+            Ok(Value::Map(map))
+        }
+        other => {
+            let list = expect_list(other, token)?;
+
+            Ok(Value::List(
+                items
+                    .iter()
+                    .fold(list, |list, item| list.cons(item.clone())),
+            ))
+        }
+    }
+}
+
+/// `(merge m ...)`, keys in later maps win.
+fn merge(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let mut merged = Map::new();
+
+    for arg in args.iter() {
+        for (key, value) in expect_map(arg, token)?.iter() {
+            merged = merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    Ok(Value::Map(merged))
+}
+
+/// Structural equality across every value type.
+fn equal(_env: &mut Environment, _token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    Ok(Value::Bool(args.windows(2).all(|pair| pair[0] == pair[1])))
+}
@@ -0,0 +1,151 @@
+use super::{expect_arity, expect_string};
+use crate::{
+    env::Environment,
+    error::EvalError,
+    value::{ErrorValue, Value},
+};
+use flop_frontend::token::Token;
+use std::rc::Rc;
+
+pub fn register(env: &mut Environment) {
+    env.register_fn("throw", throw);
+    env.register_fn("error", error);
+    env.register_fn("error?", is_error);
+    env.register_fn("error-message", error_message);
+    env.register_fn("error-data", error_data);
+    env.register_fn("invoke-restart", invoke_restart);
+    env.register_fn("compute-restarts", compute_restarts);
+}
+
+fn expect_error(value: &Value, token: &Token) -> Result<Rc<ErrorValue>, EvalError> {
+    match value {
+        Value::Error(error) => Ok(error.clone()),
+        other => Err(EvalError::TypeError {
+            token: token.clone(),
+            expected: "ERROR",
+            found: other.type_name(),
+        }),
+    }
+}
+
+/// `(throw value)` raises any value. Caught native errors are rethrown as they
+/// were, so an uncaught one is still reported where it happened.
+fn throw(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let value = args.into_iter().next().unwrap_or(Value::Nil);
+
+    if let Value::Error(error) = &value {
+        if let Some(native) = &error.native {
+            return Err(native.clone());
+        }
+    }
+
+    Err(EvalError::Thrown {
+        token: token.clone(),
+        value: Box::new(value),
+    })
+}
+
+/// `(error message)` or `(error message data)`
+fn error(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(EvalError::ArityError {
+            token: token.clone(),
+            expected: "1 or 2".to_string(),
+            found: args.len(),
+        });
+    }
+
+    let message = expect_string(&args[0], token)?;
+    let data = args.get(1).cloned().unwrap_or(Value::Nil);
+
+    Ok(Value::Error(Rc::new(ErrorValue::new(message, data))))
+}
+
+fn is_error(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    Ok(Value::Bool(matches!(args[0], Value::Error(_))))
+}
+
+fn error_message(
+    _env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    Ok(Value::String(
+        expect_error(&args[0], token)?.message.clone(),
+    ))
+}
+
+fn error_data(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    Ok(expect_error(&args[0], token)?.data.clone())
+}
+
+/// `(invoke-restart :name args...)` unwinds to the innermost active restart with
+/// that name, passing it the arguments.
+fn invoke_restart(
+    env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    let mut args = args.into_iter();
+
+    let name = match args.next() {
+        Some(Value::Keyword(name)) => name,
+        Some(other) => {
+            return Err(EvalError::TypeError {
+                token: token.clone(),
+                expected: "KEYWORD",
+                found: other.type_name(),
+            })
+        }
+        None => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "at least 1".to_string(),
+                found: 0,
+            })
+        }
+    };
+
+    let Some(restart) = env
+        .restarts
+        .iter()
+        .rev()
+        .find(|restart| restart.name == name)
+    else {
+        return Err(EvalError::ValueError {
+            token: token.clone(),
+            message: format!("no restart named :{} is active", name),
+        });
+    };
+
+    let id = restart.id;
+
+    env.restart_args = args.collect();
+
+    Err(EvalError::RestartInvoked { name, id })
+}
+
+/// The names of the active restarts, innermost first.
+fn compute_restarts(
+    env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 0)?;
+
+    Ok(Value::List(
+        env.restarts
+            .iter()
+            .rev()
+            .map(|restart| Value::Keyword(restart.name.clone()))
+            .collect(),
+    ))
+}
@@ -0,0 +1,104 @@
+use super::{expect_arity, expect_integer, expect_list};
+use crate::{env::Environment, error::EvalError, value::Value};
+use flop_frontend::token::Token;
+
+pub fn register(env: &mut Environment) {
+    env.register_fn("list", list);
+    env.register_fn("cons", cons);
+    env.register_fn("car", first);
+    env.register_fn("first", first);
+    env.register_fn("cdr", rest);
+    env.register_fn("rest", rest);
+    env.register_fn("nth", nth);
+    env.register_fn("length", length);
+    env.register_fn("empty?", is_empty);
+    env.register_fn("append", append);
+    env.register_fn("reverse", reverse);
+}
+
+fn list(_env: &mut Environment, _token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    Ok(Value::List(args.into_iter().collect()))
+}
+
+fn cons(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let tail = expect_list(&args[1], token)?;
+
+    Ok(Value::List(tail.cons(args[0].clone())))
+}
+
+fn first(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let list = expect_list(&args[0], token)?;
+
+    Ok(list.first().cloned().unwrap_or(Value::Nil))
+}
+
+fn rest(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let list = expect_list(&args[0], token)?;
+
+    Ok(Value::List(list.rest()))
+}
+
+fn nth(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let list = expect_list(&args[0], token)?;
+    let index = expect_integer(&args[1], token)?;
+
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| list.iter().nth(i))
+        .cloned()
+        .ok_or(EvalError::IndexError {
+            token: token.clone(),
+            index,
+            length: list.len(),
+        })
+}
+
+/// Strings are measured in characters rather than bytes.
+fn length(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    if let Value::String(text) = &args[0] {
+        return Ok(Value::Integer(text.chars().count() as i64));
+    }
+
+    let list = expect_list(&args[0], token)?;
+
+    Ok(Value::Integer(list.len() as i64))
+}
+
+fn is_empty(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let list = expect_list(&args[0], token)?;
+
+    Ok(Value::Bool(list.is_empty()))
+}
+
+fn append(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let mut lists = args
+        .iter()
+        .map(|arg| expect_list(arg, token))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let last = lists.pop().unwrap_or_default();
+
+    let appended = lists.iter().rev().fold(last, |acc, list| list.append(&acc));
+
+    Ok(Value::List(appended))
+}
+
+fn reverse(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let list = expect_list(&args[0], token)?;
+
+    Ok(Value::List(list.reverse()))
+}
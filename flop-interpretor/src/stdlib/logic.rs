@@ -0,0 +1,34 @@
+use super::expect_arity;
+use crate::{env::Environment, error::EvalError, value::Value};
+use flop_frontend::token::Token;
+
+pub fn register(env: &mut Environment) {
+    env.register_fn("not", not);
+    env.register_fn("nil?", is_nil);
+    env.register_fn("type", type_of);
+}
+
+fn not(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    Ok(Value::Bool(!args[0].is_truthy()))
+}
+
+fn is_nil(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    Ok(Value::Bool(args[0] == Value::Nil))
+}
+
+/// The type of a value as a keyword, `:integer` or `:string` for built-in values
+/// and the type name, like `:Point`, for structs.
+fn type_of(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let name = match &args[0] {
+        Value::Struct(instance) => instance.kind.name.clone(),
+        other => other.type_name().to_lowercase(),
+    };
+
+    Ok(Value::Keyword(name))
+}
@@ -0,0 +1,255 @@
+use super::{expect_arity, expect_number};
+use crate::{
+    env::Environment,
+    error::EvalError,
+    operation::{Comparison, Number, Operation},
+    value::Value,
+};
+use flop_frontend::token::Token;
+
+pub fn register(env: &mut Environment) {
+    env.register_fn("+", add);
+    env.register_fn("-", subtract);
+    env.register_fn("/", divide);
+    env.register_fn("*", multiply);
+    env.register_fn("quot", quotient);
+    env.register_fn("rem", remainder);
+    env.register_fn("mod", modulo);
+    env.register_fn("pow", power);
+    env.register_fn("==", equal);
+    env.register_fn("!=", not_equal);
+    env.register_fn(">=", greater_than_or_equal);
+    env.register_fn("<=", less_than_or_equal);
+    env.register_fn(">", greater_than);
+    env.register_fn("<", less_than);
+    env.register_fn("abs", abs);
+    env.register_fn("min", min);
+    env.register_fn("max", max);
+    env.register_fn("sqrt", sqrt);
+}
+
+fn numbers(token: &Token, args: &[Value]) -> Result<Vec<Number>, EvalError> {
+    args.iter().map(|arg| expect_number(arg, token)).collect()
+}
+
+fn at_least_one(token: &Token, args: &[Value]) -> Result<(), EvalError> {
+    if args.is_empty() {
+        Err(EvalError::ArityError {
+            token: token.clone(),
+            expected: "at least 1".to_string(),
+            found: 0,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn apply(operation: &Operation, token: &Token, a: Number, b: Number) -> Result<Number, EvalError> {
+    operation
+        .apply(a, b)
+        .map_err(|message| EvalError::ArithmeticError {
+            token: token.clone(),
+            message,
+        })
+}
+
+/// Folds the arguments left to right with `operation`, starting from `init`.
+fn fold(
+    operation: Operation,
+    init: Number,
+    token: &Token,
+    operands: Vec<Number>,
+) -> Result<Value, EvalError> {
+    operands
+        .into_iter()
+        .try_fold(init, |acc, operand| apply(&operation, token, acc, operand))
+        .map(Value::from)
+}
+
+/// Folds from the first argument; a single argument is combined with `identity`
+/// instead, so `(- x)` negates and `(/ x)` takes the reciprocal.
+fn fold_inverse(
+    operation: Operation,
+    identity: Number,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    at_least_one(token, &args)?;
+
+    let mut operands = numbers(token, &args)?;
+
+    let init = match operands.len() {
+        1 => identity,
+        _ => operands.remove(0),
+    };
+
+    fold(operation, init, token, operands)
+}
+
+/// Strict binary operation such as `(mod a b)`.
+fn binary(operation: Operation, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let a = expect_number(&args[0], token)?;
+    let b = expect_number(&args[1], token)?;
+
+    apply(&operation, token, a, b).map(Value::from)
+}
+
+/// Compares every neighbouring pair, so `(< a b c)` means `a < b` and `b < c`.
+fn chain(comparison: Comparison, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    at_least_one(token, &args)?;
+
+    let operands = numbers(token, &args)?;
+
+    let holds = operands
+        .windows(2)
+        .all(|pair| comparison.test(pair[0], pair[1]));
+
+    Ok(Value::Bool(holds))
+}
+
+fn add(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    fold(
+        Operation::Add,
+        Number::Integer(0),
+        token,
+        numbers(token, &args)?,
+    )
+}
+
+fn multiply(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    fold(
+        Operation::Multiply,
+        Number::Integer(1),
+        token,
+        numbers(token, &args)?,
+    )
+}
+
+fn subtract(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    fold_inverse(Operation::Subtract, Number::Integer(0), token, args)
+}
+
+fn divide(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    fold_inverse(Operation::Divide, Number::Integer(1), token, args)
+}
+
+fn quotient(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    binary(Operation::Quotient, token, args)
+}
+
+/// Remainder with the sign of the dividend.
+fn remainder(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    binary(Operation::Remainder, token, args)
+}
+
+/// Modulus with the sign of the divisor.
+fn modulo(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    binary(Operation::Modulo, token, args)
+}
+
+fn power(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    binary(Operation::Power, token, args)
+}
+
+fn equal(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    chain(Comparison::Equal, token, args)
+}
+
+/// `(!= a b ...)` is true when no two arguments are numerically equal, so
+/// `(!= 1 2 1)` is false.
+fn not_equal(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    at_least_one(token, &args)?;
+
+    let operands = numbers(token, &args)?;
+
+    let distinct = operands.iter().enumerate().all(|(index, &a)| {
+        operands[index + 1..]
+            .iter()
+            .all(|&b| Comparison::NotEqual.test(a, b))
+    });
+
+    Ok(Value::Bool(distinct))
+}
+
+fn greater_than(
+    _env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    chain(Comparison::GreaterThan, token, args)
+}
+
+fn greater_than_or_equal(
+    _env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    chain(Comparison::GreaterThanOrEqual, token, args)
+}
+
+fn less_than(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    chain(Comparison::LessThan, token, args)
+}
+
+fn less_than_or_equal(
+    _env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    chain(Comparison::LessThanOrEqual, token, args)
+}
+
+fn abs(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    match expect_number(&args[0], token)? {
+        Number::Integer(n) => {
+            n.checked_abs()
+                .map(Value::Integer)
+                .ok_or(EvalError::ArithmeticError {
+                    token: token.clone(),
+                    message: "integer overflow",
+                })
+        }
+        Number::Float(n) => Ok(Value::Float(n.abs())),
+    }
+}
+
+/// Picks the argument whose comparison with every other one holds.
+fn extreme(comparison: Comparison, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    at_least_one(token, &args)?;
+
+    let operands = numbers(token, &args)?;
+
+    let picked = operands
+        .into_iter()
+        .reduce(|best, n| if comparison.test(n, best) { n } else { best })
+        .map(Value::from);
+
+    Ok(picked.unwrap_or(Value::Nil))
+}
+
+fn min(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    extreme(Comparison::LessThan, token, args)
+}
+
+fn max(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    extreme(Comparison::GreaterThan, token, args)
+}
+
+fn sqrt(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let n = expect_number(&args[0], token)?.as_f64();
+
+    if n < 0.0 {
+        return Err(EvalError::ArithmeticError {
+            token: token.clone(),
+            message: "square root of a negative number",
+        });
+    }
+
+    Ok(Value::Float(n.sqrt()))
+}
@@ -0,0 +1,92 @@
+use crate::{
+    env::Environment, error::EvalError, function::Function, list::List, operation::Number,
+    value::Value,
+};
+use flop_frontend::token::Token;
+
+mod collection;
+mod exception;
+mod list;
+mod logic;
+mod math;
+mod seq;
+mod string;
+mod system;
+
+/// Registers every builtin with the environment.
+pub fn register(env: &mut Environment) {
+    math::register(env);
+    list::register(env);
+    seq::register(env);
+    string::register(env);
+    collection::register(env);
+    logic::register(env);
+    exception::register(env);
+    system::register(env);
+}
+
+pub fn expect_arity(token: &Token, args: &[Value], arity: usize) -> Result<(), EvalError> {
+    if args.len() == arity {
+        Ok(())
+    } else {
+        Err(EvalError::ArityError {
+            token: token.clone(),
+            expected: arity.to_string(),
+            found: args.len(),
+        })
+    }
+}
+
+pub(crate) fn expect_integer(value: &Value, token: &Token) -> Result<i64, EvalError> {
+    match value {
+        Value::Integer(n) => Ok(*n),
+        other => Err(EvalError::TypeError {
+            token: token.clone(),
+            expected: "INTEGER",
+            found: other.type_name(),
+        }),
+    }
+}
+
+pub(crate) fn expect_number(value: &Value, token: &Token) -> Result<Number, EvalError> {
+    value.as_number().ok_or_else(|| EvalError::TypeError {
+        token: token.clone(),
+        expected: "NUMBER",
+        found: value.type_name(),
+    })
+}
+
+/// `nil` is treated as the empty list.
+pub(crate) fn expect_list(value: &Value, token: &Token) -> Result<List, EvalError> {
+    match value {
+        Value::List(list) => Ok(list.clone()),
+        Value::Nil => Ok(List::new()),
+        other => Err(EvalError::TypeError {
+            token: token.clone(),
+            expected: "LIST",
+            found: other.type_name(),
+        }),
+    }
+}
+
+pub(crate) fn expect_function(value: &Value, token: &Token) -> Result<Function, EvalError> {
+    match value {
+        Value::Function(function) => Ok(function.clone()),
+        other => Err(EvalError::TypeError {
+            token: token.clone(),
+            expected: "FUNCTION",
+            found: other.type_name(),
+        }),
+    }
+}
+
+pub(crate) fn expect_string(value: &Value, token: &Token) -> Result<String, EvalError> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        other => Err(EvalError::TypeError {
+            token: token.clone(),
+            expected: "STRING",
+            found: other.type_name(),
+        }),
+    }
+}
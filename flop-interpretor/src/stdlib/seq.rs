@@ -0,0 +1,276 @@
+use super::{expect_arity, expect_function, expect_integer, expect_list};
+use crate::{
+    env::Environment, error::EvalError, evaluation::apply, function::Function, list::List,
+    value::Value,
+};
+use flop_frontend::token::Token;
+use std::cmp::Ordering;
+
+pub fn register(env: &mut Environment) {
+    env.register_fn("map", map);
+    env.register_fn("filter", filter);
+    env.register_fn("reduce", reduce);
+    env.register_fn("fold", reduce);
+    env.register_fn("range", range);
+    env.register_fn("apply", apply_builtin);
+    env.register_fn("sort", sort);
+    env.register_fn("zip", zip);
+    env.register_fn("any", any);
+    env.register_fn("every", every);
+}
+
+fn expect_at_least(token: &Token, args: &[Value], arity: usize) -> Result<(), EvalError> {
+    if args.len() >= arity {
+        Ok(())
+    } else {
+        Err(EvalError::ArityError {
+            token: token.clone(),
+            expected: format!("at least {}", arity),
+            found: args.len(),
+        })
+    }
+}
+
+/// `(map f coll & colls)` walks every collection in lockstep, stopping at the shortest.
+fn map(env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_at_least(token, &args, 2)?;
+
+    let function = expect_function(&args[0], token)?;
+
+    let mut lists = args[1..]
+        .iter()
+        .map(|arg| expect_list(arg, token))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut results = Vec::new();
+
+    while lists.iter().all(|list| !list.is_empty()) {
+        let call_args = lists
+            .iter()
+            .filter_map(|list| list.first().cloned())
+            .collect();
+
+        results.push(apply(&function, token, call_args, env)?);
+
+        lists = lists.iter().map(List::rest).collect();
+    }
+
+    Ok(Value::List(results.into_iter().collect()))
+}
+
+fn filter(env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let function = expect_function(&args[0], token)?;
+    let list = expect_list(&args[1], token)?;
+
+    let mut results = Vec::new();
+
+    for item in list.iter() {
+        if apply(&function, token, vec![item.clone()], env)?.is_truthy() {
+            results.push(item.clone());
+        }
+    }
+
+    Ok(Value::List(results.into_iter().collect()))
+}
+
+/// `(reduce f coll)` seeds with the first element, `(reduce f init coll)` with `init`.
+fn reduce(env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let (function, init, list) = match args.as_slice() {
+        [function, coll] => {
+            let list = expect_list(coll, token)?;
+            let init = list.first().cloned().unwrap_or(Value::Nil);
+            (function, init, list.rest())
+        }
+        [function, init, coll] => (function, init.clone(), expect_list(coll, token)?),
+        _ => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "2 or 3".to_string(),
+                found: args.len(),
+            })
+        }
+    };
+
+    let function = expect_function(function, token)?;
+
+    list.iter().try_fold(init, |acc, item| {
+        apply(&function, token, vec![acc, item.clone()], env)
+    })
+}
+
+/// `(range end)`, `(range start end)` or `(range start end step)`, end exclusive.
+fn range(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let bounds = args
+        .iter()
+        .map(|arg| expect_integer(arg, token))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (start, end, step) = match bounds.as_slice() {
+        [end] => (0, *end, 1),
+        [start, end] => (*start, *end, 1),
+        [start, end, step] => (*start, *end, *step),
+        _ => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "1 to 3".to_string(),
+                found: args.len(),
+            })
+        }
+    };
+
+    if step == 0 {
+        return Err(EvalError::ValueError {
+            token: token.clone(),
+            message: "range step must not be 0".to_string(),
+        });
+    }
+
+    let mut values = Vec::new();
+    let mut current = start;
+
+    while (step > 0 && current < end) || (step < 0 && current > end) {
+        values.push(Value::Integer(current));
+
+        // the range ends at the last value before the integers run out
+        match current.checked_add(step) {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+
+    Ok(Value::List(values.into_iter().collect()))
+}
+
+/// `(apply f args... coll)` spreads the final list into the call.
+fn apply_builtin(
+    env: &mut Environment,
+    token: &Token,
+    mut args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    expect_at_least(token, &args, 2)?;
+
+    let function = expect_function(&args.remove(0), token)?;
+
+    let spread = expect_list(&args.pop().unwrap_or(Value::Nil), token)?;
+
+    args.extend(spread.iter().cloned());
+
+    apply(&function, token, args, env)
+}
+
+/// `(sort coll)` uses the natural ordering, `(sort comp coll)` a "less than" function.
+fn sort(env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let (comparator, list) = match args.as_slice() {
+        [coll] => (None, expect_list(coll, token)?),
+        [comparator, coll] => (
+            Some(expect_function(comparator, token)?),
+            expect_list(coll, token)?,
+        ),
+        _ => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "1 or 2".to_string(),
+                found: args.len(),
+            })
+        }
+    };
+
+    let mut items: Vec<Value> = list.iter().cloned().collect();
+    let mut failure = None;
+
+    items.sort_by(|a, b| {
+        if failure.is_some() {
+            return Ordering::Equal;
+        }
+
+        let ordering = match &comparator {
+            None => a.compare(b).ok_or_else(|| EvalError::TypeError {
+                token: token.clone(),
+                expected: a.type_name(),
+                found: b.type_name(),
+            }),
+            Some(function) => compare_with(function, token, a, b, env),
+        };
+
+        ordering.unwrap_or_else(|err| {
+            failure = Some(err);
+            Ordering::Equal
+        })
+    });
+
+    match failure {
+        Some(err) => Err(err),
+        None => Ok(Value::List(items.into_iter().collect())),
+    }
+}
+
+/// Orders `a` and `b` with a "less than" function, calling it in both directions.
+fn compare_with(
+    function: &Function,
+    token: &Token,
+    a: &Value,
+    b: &Value,
+    env: &mut Environment,
+) -> Result<Ordering, EvalError> {
+    if apply(function, token, vec![a.clone(), b.clone()], env)?.is_truthy() {
+        Ok(Ordering::Less)
+    } else if apply(function, token, vec![b.clone(), a.clone()], env)?.is_truthy() {
+        Ok(Ordering::Greater)
+    } else {
+        Ok(Ordering::Equal)
+    }
+}
+
+fn zip(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let mut lists = args
+        .iter()
+        .map(|arg| expect_list(arg, token))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut results = Vec::new();
+
+    while !lists.is_empty() && lists.iter().all(|list| !list.is_empty()) {
+        let tuple = lists
+            .iter()
+            .filter_map(|list| list.first().cloned())
+            .collect();
+
+        results.push(Value::List(tuple));
+
+        lists = lists.iter().map(List::rest).collect();
+    }
+
+    Ok(Value::List(results.into_iter().collect()))
+}
+
+fn any(env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let function = expect_function(&args[0], token)?;
+    let list = expect_list(&args[1], token)?;
+
+    for item in list.iter() {
+        if apply(&function, token, vec![item.clone()], env)?.is_truthy() {
+            return Ok(Value::Bool(true));
+        }
+    }
+
+    Ok(Value::Bool(false))
+}
+
+fn every(env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let function = expect_function(&args[0], token)?;
+    let list = expect_list(&args[1], token)?;
+
+    for item in list.iter() {
+        if !apply(&function, token, vec![item.clone()], env)?.is_truthy() {
+            return Ok(Value::Bool(false));
+        }
+    }
+
+    Ok(Value::Bool(true))
+}
@@ -0,0 +1,238 @@
+use super::{expect_arity, expect_integer, expect_list, expect_number, expect_string};
+use crate::{env::Environment, error::EvalError, value::Value};
+use flop_frontend::token::Token;
+
+pub fn register(env: &mut Environment) {
+    env.register_fn("str", str);
+    env.register_fn("substring", substring);
+    env.register_fn("split", split);
+    env.register_fn("join", join);
+    env.register_fn("trim", trim);
+    env.register_fn("upper", upper);
+    env.register_fn("lower", lower);
+    env.register_fn("starts-with?", starts_with);
+    env.register_fn("ends-with?", ends_with);
+    env.register_fn("replace", replace);
+    env.register_fn("format", format);
+    env.register_fn("string->number", string_to_number);
+    env.register_fn("number->string", number_to_string);
+}
+
+/// `(str a b ...)` concatenates the plain text of every argument.
+fn str(_env: &mut Environment, _token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let text = args.iter().map(Value::to_plain_string).collect::<String>();
+
+    Ok(Value::String(text))
+}
+
+/// `(substring s start end?)` with indices counted in characters, end exclusive.
+fn substring(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let (text, start, end) = match args.as_slice() {
+        [text, start] => (
+            expect_string(text, token)?,
+            expect_integer(start, token)?,
+            None,
+        ),
+        [text, start, end] => (
+            expect_string(text, token)?,
+            expect_integer(start, token)?,
+            Some(expect_integer(end, token)?),
+        ),
+        _ => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "2 or 3".to_string(),
+                found: args.len(),
+            })
+        }
+    };
+
+    let length = text.chars().count();
+    let end = end.unwrap_or(length as i64);
+
+    let in_bounds = |index: i64| usize::try_from(index).ok().filter(|i| *i <= length);
+
+    let (start, end) = match (in_bounds(start), in_bounds(end)) {
+        (Some(start), Some(end)) if start <= end => (start, end),
+        (Some(_), Some(_)) | (None, _) => {
+            return Err(EvalError::IndexError {
+                token: token.clone(),
+                index: start,
+                length,
+            })
+        }
+        (_, None) => {
+            return Err(EvalError::IndexError {
+                token: token.clone(),
+                index: end,
+                length,
+            })
+        }
+    };
+
+    let slice = text.chars().skip(start).take(end - start).collect();
+
+    Ok(Value::String(slice))
+}
+
+/// `(split s sep)`, an empty separator splits into single characters.
+fn split(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let text = expect_string(&args[0], token)?;
+    let separator = expect_string(&args[1], token)?;
+
+    let parts = if separator.is_empty() {
+        text.chars()
+            .map(|ch| Value::String(ch.to_string()))
+            .collect()
+    } else {
+        text.split(separator.as_str())
+            .map(|part| Value::String(part.to_string()))
+            .collect()
+    };
+
+    Ok(Value::List(parts))
+}
+
+/// `(join coll)` or `(join sep coll)`.
+fn join(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let (separator, list) = match args.as_slice() {
+        [coll] => (String::new(), expect_list(coll, token)?),
+        [separator, coll] => (expect_string(separator, token)?, expect_list(coll, token)?),
+        _ => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "1 or 2".to_string(),
+                found: args.len(),
+            })
+        }
+    };
+
+    let joined = list
+        .iter()
+        .map(Value::to_plain_string)
+        .collect::<Vec<_>>()
+        .join(&separator);
+
+    Ok(Value::String(joined))
+}
+
+fn trim(text: String) -> String {
+    text.trim().to_string()
+}
+
+fn upper(text: String) -> String {
+    text.to_uppercase()
+}
+
+fn lower(text: String) -> String {
+    text.to_lowercase()
+}
+
+pub(super) fn contains(
+    _env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 2)?;
+
+    let text = expect_string(&args[0], token)?;
+    let pattern = expect_string(&args[1], token)?;
+
+    Ok(Value::Bool(text.contains(pattern.as_str())))
+}
+
+fn starts_with(text: String, prefix: String) -> bool {
+    text.starts_with(prefix.as_str())
+}
+
+fn ends_with(text: String, suffix: String) -> bool {
+    text.ends_with(suffix.as_str())
+}
+
+/// `(replace s from to)` replaces every occurrence of `from`.
+fn replace(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 3)?;
+
+    let text = expect_string(&args[0], token)?;
+    let from = expect_string(&args[1], token)?;
+    let to = expect_string(&args[2], token)?;
+
+    if from.is_empty() {
+        return Err(EvalError::ValueError {
+            token: token.clone(),
+            message: "replace pattern must not be empty".to_string(),
+        });
+    }
+
+    Ok(Value::String(text.replace(from.as_str(), &to)))
+}
+
+/// `(format "{} and {}" a b)` fills each `{}` with the next argument.
+fn format(_env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    let (template, values) = match args.split_first() {
+        Some((template, values)) => (expect_string(template, token)?, values),
+        None => {
+            return Err(EvalError::ArityError {
+                token: token.clone(),
+                expected: "at least 1".to_string(),
+                found: 0,
+            })
+        }
+    };
+
+    let pieces: Vec<&str> = template.split("{}").collect();
+
+    if pieces.len() - 1 != values.len() {
+        return Err(EvalError::ValueError {
+            token: token.clone(),
+            message: format!(
+                "format string has {} placeholders but {} values were given",
+                pieces.len() - 1,
+                values.len()
+            ),
+        });
+    }
+
+    let mut formatted = String::from(pieces[0]);
+
+    for (value, piece) in values.iter().zip(&pieces[1..]) {
+        formatted.push_str(&value.to_plain_string());
+        formatted.push_str(piece);
+    }
+
+    Ok(Value::String(formatted))
+}
+
+/// Returns `nil` when the string is not a number.
+fn string_to_number(
+    _env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let text = expect_string(&args[0], token)?;
+
+    let text = text.trim();
+
+    let number = match text.parse::<i64>() {
+        Ok(n) => Value::Integer(n),
+        Err(_) => text.parse::<f64>().map(Value::Float).unwrap_or(Value::Nil),
+    };
+
+    Ok(number)
+}
+
+fn number_to_string(
+    _env: &mut Environment,
+    token: &Token,
+    args: Vec<Value>,
+) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let number = Value::from(expect_number(&args[0], token)?);
+
+    Ok(Value::String(number.to_string()))
+}
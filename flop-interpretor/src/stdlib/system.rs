@@ -0,0 +1,28 @@
+use super::{expect_arity, expect_string};
+use crate::{env::Environment, error::EvalError, module::load_file, value::Value};
+use flop_frontend::token::Token;
+
+pub fn register(env: &mut Environment) {
+    env.register_fn("load", load);
+}
+
+/// `(load "path.ls")` evaluates a file every time it is called and returns its
+/// last value. Relative paths start from the directory of the calling file.
+/// Nested loads count towards the depth limit, so a file loading itself fails
+/// instead of overflowing the stack.
+fn load(env: &mut Environment, token: &Token, args: Vec<Value>) -> Result<Value, EvalError> {
+    expect_arity(token, &args, 1)?;
+
+    let path = expect_string(&args[0], token)?;
+
+    let path = match token.namespace.parent() {
+        Some(directory) => directory.join(path),
+        None => path.into(),
+    };
+
+    env.enter_call(token)?;
+    let result = load_file(&path, token, env);
+    env.leave_call();
+
+    result
+}
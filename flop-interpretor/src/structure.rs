@@ -0,0 +1,168 @@
+use crate::{error::EvalError, stdlib::expect_arity, value::Value};
+use flop_frontend::token::Token;
+use std::rc::Rc;
+
+/// A record type declared with `defstruct`, or one variant of a `defenum`.
+#[derive(Debug)]
+pub struct StructType {
+    /// `module/Name` for types declared inside a module.
+    pub name: String,
+    pub fields: Vec<String>,
+    /// The enum this type is a variant of.
+    pub enumeration: Option<String>,
+}
+
+impl StructType {
+    pub fn field_index(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|name| name == field)
+    }
+
+    /// Whether instances of `other` belong to this type. Redefining a struct with
+    /// other fields makes a new type, instances of the old one are not accepted
+    /// by the new accessors.
+    pub fn is(&self, other: &StructType) -> bool {
+        std::ptr::eq(self, other)
+            || (self.name == other.name
+                && self.fields == other.fields
+                && self.enumeration == other.enumeration)
+    }
+}
+
+/// An instance of a struct type, holding one value per field in declaration order.
+#[derive(Debug, Clone)]
+pub struct StructValue {
+    pub kind: Rc<StructType>,
+    pub values: Vec<Value>,
+}
+
+impl StructValue {
+    /// A new instance of a type built outside of Flop, like values converted from
+    /// Rust. Instances compare equal to those of the Flop type of the same name.
+    pub fn build(
+        name: &str,
+        enumeration: Option<&str>,
+        fields: &[&str],
+        values: Vec<Value>,
+    ) -> Value {
+        let kind = StructType {
+            name: name.to_string(),
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            enumeration: enumeration.map(str::to_string),
+        };
+
+        Value::Struct(Rc::new(Self {
+            kind: Rc::new(kind),
+            values,
+        }))
+    }
+
+    /// The instance `value` holds when it is of the type `name`, qualified with its
+    /// module when it was declared inside one.
+    pub fn of<'a>(value: &'a Value, name: &str) -> Option<&'a Self> {
+        match value {
+            Value::Struct(instance) if instance.kind.name == name => Some(instance),
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.kind
+            .field_index(field)
+            .map(|index| &self.values[index])
+    }
+
+    /// A copy with `field` replaced, `None` when the type has no such field.
+    pub fn with(&self, field: &str, value: Value) -> Option<Self> {
+        let index = self.kind.field_index(field)?;
+
+        let mut values = self.values.clone();
+        values[index] = value;
+
+        Some(Self {
+            kind: self.kind.clone(),
+            values,
+        })
+    }
+}
+
+/// The functions `defstruct` generates for a type.
+#[derive(Debug, Clone, Copy)]
+pub enum StructOp {
+    /// `(Name field...)`
+    Construct,
+    /// `(Name? value)`
+    Predicate,
+    /// `(Name-field value)`
+    Field(usize),
+    /// `(Enum? value)`, whether the value is any variant of the enum `kind` belongs to
+    Member,
+}
+
+impl StructOp {
+    pub fn call(
+        self,
+        kind: &Rc<StructType>,
+        token: &Token,
+        args: Vec<Value>,
+    ) -> Result<Value, EvalError> {
+        match self {
+            StructOp::Construct => {
+                expect_arity(token, &args, kind.fields.len())?;
+
+                Ok(Value::Struct(Rc::new(StructValue {
+                    kind: kind.clone(),
+                    values: args,
+                })))
+            }
+            StructOp::Predicate => {
+                expect_arity(token, &args, 1)?;
+
+                Ok(Value::Bool(
+                    matches!(&args[0], Value::Struct(value) if value.kind.is(kind)),
+                ))
+            }
+            StructOp::Member => {
+                expect_arity(token, &args, 1)?;
+
+                Ok(Value::Bool(matches!(
+                    &args[0],
+                    Value::Struct(value)
+                        if value.kind.enumeration.is_some()
+                            && value.kind.enumeration == kind.enumeration
+                )))
+            }
+            StructOp::Field(index) => {
+                expect_arity(token, &args, 1)?;
+
+                let value = match &args[0] {
+                    Value::Struct(value) => value,
+                    other => {
+                        return Err(EvalError::TypeError {
+                            token: token.clone(),
+                            expected: "STRUCT",
+                            found: other.type_name(),
+                        })
+                    }
+                };
+
+                if let Some(field) = value.values.get(index).filter(|_| value.kind.is(kind)) {
+                    return Ok(field.clone());
+                }
+
+                let message = if value.kind.name != kind.name {
+                    format!("expected a {}, found a {}", kind.name, value.kind.name)
+                } else {
+                    format!(
+                        "this {} was made by an older definition of {}, {} reads the current one",
+                        value.kind.name, kind.name, token.token
+                    )
+                };
+
+                Err(EvalError::ValueError {
+                    token: token.clone(),
+                    message,
+                })
+            }
+        }
+    }
+}
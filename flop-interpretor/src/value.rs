@@ -0,0 +1,245 @@
+use crate::{
+    error::EvalError,
+    function::Function,
+    list::List,
+    map::{Map, Set},
+    native::FromValue,
+    operation::Number,
+    structure::StructValue,
+};
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
+
+/// An error raised with `throw`, or a native failure caught by `try`.
+#[derive(Debug, Clone)]
+pub struct ErrorValue {
+    pub message: String,
+    pub data: Value,
+    /// The original error of a native failure, rethrown as is so it keeps its span.
+    pub native: Option<EvalError>,
+}
+
+impl ErrorValue {
+    pub fn new(message: String, data: Value) -> Self {
+        Self {
+            message,
+            data,
+            native: None,
+        }
+    }
+
+    /// Wraps a native error, its data is a map holding the error `:type`.
+    pub fn from_native(error: EvalError) -> Self {
+        let data = Map::new().insert(
+            Value::Keyword("type".to_string()),
+            Value::Keyword(error.kind().to_string()),
+        );
+
+        Self {
+            message: error.to_string(),
+            data: Value::Map(data),
+            native: Some(error),
+        }
+    }
+}
+
+/// A fully evaluated Flop value.
+///
+/// Values compare and hash structurally so any of them can be used as a map key
+/// or set member.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Keyword(String),
+    List(List),
+    Map(Map<Value, Value>),
+    Set(Set<Value>),
+    Function(Function),
+    Error(Rc<ErrorValue>),
+    Struct(Rc<StructValue>),
+}
+
+impl Value {
+    /// Converts to a Rust value, `None` when the value doesn't fit `T`.
+    pub fn extract<T: FromValue>(&self) -> Option<T> {
+        T::from_value(self)
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "NIL",
+            Value::Bool(_) => "BOOLEAN",
+            Value::Integer(_) => "INTEGER",
+            Value::Float(_) => "FLOAT",
+            Value::String(_) => "STRING",
+            Value::Keyword(_) => "KEYWORD",
+            Value::List(_) => "LIST",
+            Value::Map(_) => "MAP",
+            Value::Set(_) => "SET",
+            Value::Function(_) => "FUNCTION",
+            Value::Error(_) => "ERROR",
+            Value::Struct(_) => "STRUCT",
+        }
+    }
+
+    /// Position of the variant when ordering values of different types.
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Nil => 0,
+            Value::Bool(_) => 1,
+            Value::Integer(_) => 2,
+            Value::Float(_) => 3,
+            Value::String(_) => 4,
+            Value::Keyword(_) => 5,
+            Value::List(_) => 6,
+            Value::Map(_) => 7,
+            Value::Set(_) => 8,
+            Value::Function(_) => 9,
+            Value::Error(_) => 10,
+            Value::Struct(_) => 11,
+        }
+    }
+
+    /// The text of a value as used by `str` and `format`: strings are not quoted.
+    pub fn to_plain_string(&self) -> String {
+        match self {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// The single truthiness rule of the language: only `false` and `nil` are falsey.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Value::Integer(n) => Some(Number::Integer(*n)),
+            Value::Float(n) => Some(Number::Float(*n)),
+            _ => None,
+        }
+    }
+
+    /// Natural ordering between values of the same type, used by `sort`.
+    /// Integers and floats compare by numeric value.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        if let (Some(a), Some(b)) = (self.as_number(), other.as_number()) {
+            a.compare(b)
+        } else if self.rank() == other.rank() {
+            Some(self.cmp(other))
+        } else {
+            None
+        }
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => Ordering::Equal,
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Keyword(a), Value::Keyword(b)) => a.cmp(b),
+            (Value::List(a), Value::List(b)) => a.cmp(b),
+            (Value::Map(a), Value::Map(b)) => a.cmp(b),
+            (Value::Set(a), Value::Set(b)) => a.cmp(b),
+            (Value::Function(a), Value::Function(b)) => a.cmp(b),
+            (Value::Struct(a), Value::Struct(b)) => a
+                .kind
+                .name
+                .cmp(&b.kind.name)
+                .then_with(|| a.kind.fields.cmp(&b.kind.fields))
+                .then_with(|| a.values.cmp(&b.values)),
+            (Value::Error(a), Value::Error(b)) => {
+                a.message.cmp(&b.message).then_with(|| a.data.cmp(&b.data))
+            }
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+
+        match self {
+            Value::Nil => {}
+            Value::Bool(b) => b.hash(state),
+            Value::Integer(n) => n.hash(state),
+            Value::Float(n) => n.to_bits().hash(state),
+            Value::String(s) | Value::Keyword(s) => s.hash(state),
+            Value::List(list) => list.hash(state),
+            Value::Map(map) => map.hash(state),
+            Value::Set(set) => set.hash(state),
+            Value::Function(function) => function.name().hash(state),
+            Value::Error(error) => error.message.hash(state),
+            Value::Struct(value) => {
+                value.kind.name.hash(state);
+                value.values.hash(state);
+            }
+        }
+    }
+}
+
+fn join<T: fmt::Display>(items: impl Iterator<Item = T>) -> String {
+    items.map(|v| v.to_string()).collect::<Vec<_>>().join(" ")
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{:?}", n),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Keyword(k) => write!(f, ":{}", k),
+            Value::List(items) => write!(f, "[{}]", join(items.iter())),
+            Value::Map(map) => {
+                let entries = map.iter().map(|(key, value)| format!("{} {}", key, value));
+                write!(f, "{{{}}}", join(entries))
+            }
+            Value::Set(set) => write!(f, "#{{{}}}", join(set.iter())),
+            Value::Function(function) => write!(f, "{}", function),
+            Value::Error(error) => write!(f, "#<error {}>", error.message),
+            Value::Struct(value) if value.values.is_empty() => write!(f, "({})", value.kind.name),
+            Value::Struct(value) => {
+                write!(f, "({} {})", value.kind.name, join(value.values.iter()))
+            }
+        }
+    }
+}
+
+impl From<Number> for Value {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Integer(n) => Value::Integer(n),
+            Number::Float(n) => Value::Float(n),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Programs that must fail, and the kind of error they fail with.
+
+use flop_interpretor::interpreter::Interpreter;
+
+fn error_kind(code: &str) -> &'static str {
+    match Interpreter::new().eval_str(code) {
+        Ok(value) => panic!("{:?} evaluated to {}", code, value),
+        Err(err) => err.kind(),
+    }
+}
+
+#[test]
+fn defstruct_rejects_repeated_fields() {
+    assert_eq!(error_kind("(defstruct P a b a)"), "syntax-error");
+}
+
+#[test]
+fn defenum_rejects_repeated_variants() {
+    assert_eq!(
+        error_kind("(defenum Shape (Circle r) (Circle w))"),
+        "syntax-error"
+    );
+}
+
+#[test]
+fn patterns_bind_each_name_once() {
+    assert_eq!(
+        error_kind(r#"(match [1 2] [[x x] "same"] [_ "diff"])"#),
+        "syntax-error"
+    );
+    assert_eq!(error_kind(r#"(defn F [a [b a]] "doc" a)"#), "syntax-error");
+    assert_eq!(error_kind(r#"(fn [a &key a] a)"#), "syntax-error");
+}
+
+#[test]
+fn parameter_sections_come_in_order() {
+    assert_eq!(error_kind("(fn [a &key b &optional c] a)"), "syntax-error");
+    assert_eq!(error_kind("(fn [a & b &key c] a)"), "syntax-error");
+    assert_eq!(
+        error_kind(r#"(defn F [a &optional b &key c] "doc" c) (F 1 2 :c)"#),
+        "value-error"
+    );
+}
+
+#[test]
+fn strings_reject_unknown_escapes() {
+    assert_eq!(error_kind(r#"(length "a\qb")"#), "syntax-error");
+    assert_eq!(error_kind(r#"(length "\x41")"#), "syntax-error");
+}
+
+#[test]
+fn missing_files_are_reported() {
+    let err = Interpreter::new()
+        .eval_file("no/such/file.ls")
+        .expect_err("the file does not exist");
+
+    assert_eq!(err.kind(), "module-error");
+}
+
+/// With no call limit, deep recursion still stops before the native stack of the
+/// thread evaluating runs out.
+#[test]
+fn deep_recursion_stops_before_the_stack_overflows() {
+    const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+    let kind = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| {
+            let mut interpreter = Interpreter::new()
+                .with_max_depth(usize::MAX)
+                .with_stack_size(STACK_SIZE);
+
+            let code = r#"(defn D [n] "deep" (if (== n 0) 0 (+ 1 (D (- n 1))))) (D 1000000)"#;
+
+            match interpreter.eval_str(code) {
+                Ok(value) => panic!("evaluated to {}", value),
+                Err(err) => err.kind(),
+            }
+        })
+        .expect("thread")
+        .join()
+        .expect("no overflow");
+
+    assert_eq!(kind, "stack-depth-exceeded");
+}
@@ -0,0 +1,18 @@
+//! Checker warnings wait on the interpreter for the host to report them.
+
+use flop_interpretor::interpreter::Interpreter;
+
+#[test]
+fn warnings_are_handed_to_the_host() {
+    let mut interpreter = Interpreter::new();
+
+    interpreter
+        .eval_str("(defenum Shape (Circle r) (Empty)) (match (Empty) [(Empty) 0])")
+        .unwrap();
+
+    let warnings = interpreter.take_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].to_string().contains("missing Circle"));
+
+    assert!(interpreter.take_warnings().is_empty());
+}
@@ -0,0 +1,563 @@
+//! The builtin functions compiled programs call, named after their Flop
+//! counterparts. Each takes the evaluated arguments like a Flop call does and
+//! must behave like the interpreter's builtin, `flop/tests/compiled.rs` runs the
+//! example scripts both ways and compares what they print.
+
+use crate::{error::Error, expect_arity, expect_at_least, value::Value};
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy)]
+enum Number {
+    Integer(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Integer(n) => n as f64,
+            Number::Float(n) => n,
+        }
+    }
+
+    fn compare(self, other: Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Integer(a), Number::Integer(b)) => Some(a.cmp(&b)),
+            (a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+        }
+    }
+}
+
+impl From<Number> for Value {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Integer(n) => Value::Integer(n),
+            Number::Float(n) => Value::Float(n),
+        }
+    }
+}
+
+fn number(value: &Value) -> Result<Number, Error> {
+    match value {
+        Value::Integer(n) => Ok(Number::Integer(*n)),
+        Value::Float(n) => Ok(Number::Float(*n)),
+        other => Err(Error::type_error("NUMBER", other)),
+    }
+}
+
+fn numbers(args: &[Value]) -> Result<Vec<Number>, Error> {
+    args.iter().map(number).collect()
+}
+
+#[derive(Clone, Copy)]
+enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Quotient,
+    Remainder,
+    Modulo,
+    Power,
+}
+
+impl Operation {
+    /// Integer arithmetic is exact and checked, a float operand makes the whole
+    /// operation floating point, as in the interpreter.
+    fn apply(self, a: Number, b: Number) -> Result<Number, Error> {
+        let divides = matches!(
+            self,
+            Operation::Divide | Operation::Quotient | Operation::Remainder | Operation::Modulo
+        );
+
+        if divides && b.as_f64() == 0.0 {
+            return Err(Error::new("arithmetic error: division by zero"));
+        }
+
+        match (a, b) {
+            (Number::Integer(a), Number::Integer(b)) => self.apply_integer(a, b),
+            (a, b) => Ok(Number::Float(self.apply_float(a.as_f64(), b.as_f64()))),
+        }
+    }
+
+    fn apply_integer(self, a: i64, b: i64) -> Result<Number, Error> {
+        let result = match self {
+            Operation::Add => a.checked_add(b),
+            Operation::Subtract => a.checked_sub(b),
+            Operation::Multiply => a.checked_mul(b),
+            Operation::Divide if a.checked_rem(b).is_some_and(|r| r != 0) => {
+                return Ok(Number::Float(a as f64 / b as f64));
+            }
+            Operation::Divide | Operation::Quotient => a.checked_div(b),
+            Operation::Remainder => a.checked_rem(b),
+            Operation::Modulo => a.checked_rem(b).map(|r| {
+                if r != 0 && (r < 0) != (b < 0) {
+                    r + b
+                } else {
+                    r
+                }
+            }),
+            Operation::Power => match u32::try_from(b) {
+                Ok(exponent) => a.checked_pow(exponent),
+                Err(_) => return Ok(Number::Float((a as f64).powf(b as f64))),
+            },
+        };
+
+        result
+            .map(Number::Integer)
+            .ok_or_else(|| Error::new("arithmetic error: integer overflow"))
+    }
+
+    fn apply_float(self, a: f64, b: f64) -> f64 {
+        match self {
+            Operation::Add => a + b,
+            Operation::Subtract => a - b,
+            Operation::Multiply => a * b,
+            Operation::Divide => a / b,
+            Operation::Quotient => (a / b).trunc(),
+            Operation::Remainder => a % b,
+            Operation::Modulo => a - b * (a / b).floor(),
+            Operation::Power => a.powf(b),
+        }
+    }
+}
+
+fn fold(operation: Operation, init: Number, operands: Vec<Number>) -> Result<Value, Error> {
+    operands
+        .into_iter()
+        .try_fold(init, |acc, operand| operation.apply(acc, operand))
+        .map(Value::from)
+}
+
+/// A single argument is combined with `identity`, so `(- x)` negates.
+fn fold_inverse(
+    name: &str,
+    operation: Operation,
+    identity: Number,
+    args: Vec<Value>,
+) -> Result<Value, Error> {
+    expect_at_least(name, &args, 1)?;
+
+    let mut operands = numbers(&args)?;
+
+    let init = match operands.len() {
+        1 => identity,
+        _ => operands.remove(0),
+    };
+
+    fold(operation, init, operands)
+}
+
+fn binary(name: &str, operation: Operation, args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity(name, &args, 2)?;
+
+    operation
+        .apply(number(&args[0])?, number(&args[1])?)
+        .map(Value::from)
+}
+
+/// Compares every neighbouring pair, so `(< a b c)` means `a < b` and `b < c`.
+fn chain(
+    name: &str,
+    args: Vec<Value>,
+    holds: fn(Option<Ordering>) -> bool,
+) -> Result<Value, Error> {
+    expect_at_least(name, &args, 1)?;
+
+    let operands = numbers(&args)?;
+
+    Ok(Value::Bool(
+        operands
+            .windows(2)
+            .all(|pair| holds(pair[0].compare(pair[1]))),
+    ))
+}
+
+/// `+`
+pub fn add(args: Vec<Value>) -> Result<Value, Error> {
+    fold(Operation::Add, Number::Integer(0), numbers(&args)?)
+}
+
+/// `*`
+pub fn multiply(args: Vec<Value>) -> Result<Value, Error> {
+    fold(Operation::Multiply, Number::Integer(1), numbers(&args)?)
+}
+
+/// `-`
+pub fn subtract(args: Vec<Value>) -> Result<Value, Error> {
+    fold_inverse("-", Operation::Subtract, Number::Integer(0), args)
+}
+
+/// `/`
+pub fn divide(args: Vec<Value>) -> Result<Value, Error> {
+    fold_inverse("/", Operation::Divide, Number::Integer(1), args)
+}
+
+pub fn quot(args: Vec<Value>) -> Result<Value, Error> {
+    binary("quot", Operation::Quotient, args)
+}
+
+pub fn rem(args: Vec<Value>) -> Result<Value, Error> {
+    binary("rem", Operation::Remainder, args)
+}
+
+/// `mod`
+pub fn modulo(args: Vec<Value>) -> Result<Value, Error> {
+    binary("mod", Operation::Modulo, args)
+}
+
+pub fn pow(args: Vec<Value>) -> Result<Value, Error> {
+    binary("pow", Operation::Power, args)
+}
+
+/// `==`
+pub fn equal(args: Vec<Value>) -> Result<Value, Error> {
+    chain("==", args, |ordering| ordering == Some(Ordering::Equal))
+}
+
+/// `!=`, true when no two arguments are numerically equal.
+pub fn not_equal(args: Vec<Value>) -> Result<Value, Error> {
+    expect_at_least("!=", &args, 1)?;
+
+    let operands = numbers(&args)?;
+
+    let distinct = operands.iter().enumerate().all(|(index, &a)| {
+        operands[index + 1..]
+            .iter()
+            .all(|&b| a.compare(b) != Some(Ordering::Equal))
+    });
+
+    Ok(Value::Bool(distinct))
+}
+
+/// `<`
+pub fn less_than(args: Vec<Value>) -> Result<Value, Error> {
+    chain("<", args, |ordering| ordering == Some(Ordering::Less))
+}
+
+/// `<=`
+pub fn less_than_or_equal(args: Vec<Value>) -> Result<Value, Error> {
+    chain("<=", args, |ordering| {
+        matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+    })
+}
+
+/// `>`
+pub fn greater_than(args: Vec<Value>) -> Result<Value, Error> {
+    chain(">", args, |ordering| ordering == Some(Ordering::Greater))
+}
+
+/// `>=`
+pub fn greater_than_or_equal(args: Vec<Value>) -> Result<Value, Error> {
+    chain(">=", args, |ordering| {
+        matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+    })
+}
+
+pub fn abs(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("abs", &args, 1)?;
+
+    match number(&args[0])? {
+        Number::Integer(n) => n
+            .checked_abs()
+            .map(Value::Integer)
+            .ok_or_else(|| Error::new("arithmetic error: integer overflow")),
+        Number::Float(n) => Ok(Value::Float(n.abs())),
+    }
+}
+
+fn extreme(name: &str, args: Vec<Value>, wanted: Ordering) -> Result<Value, Error> {
+    expect_at_least(name, &args, 1)?;
+
+    let operands = numbers(&args)?;
+
+    let best = operands[1..].iter().fold(operands[0], |best, &operand| {
+        if operand.compare(best) == Some(wanted) {
+            operand
+        } else {
+            best
+        }
+    });
+
+    Ok(Value::from(best))
+}
+
+pub fn min(args: Vec<Value>) -> Result<Value, Error> {
+    extreme("min", args, Ordering::Less)
+}
+
+pub fn max(args: Vec<Value>) -> Result<Value, Error> {
+    extreme("max", args, Ordering::Greater)
+}
+
+pub fn sqrt(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("sqrt", &args, 1)?;
+
+    Ok(Value::Float(number(&args[0])?.as_f64().sqrt()))
+}
+
+pub fn not(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("not", &args, 1)?;
+
+    Ok(Value::Bool(!args[0].is_truthy()))
+}
+
+/// `nil?`
+pub fn is_nil(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("nil?", &args, 1)?;
+
+    Ok(Value::Bool(args[0] == Value::Nil))
+}
+
+/// `=`, structural equality of any values.
+pub fn same(args: Vec<Value>) -> Result<Value, Error> {
+    Ok(Value::Bool(args.windows(2).all(|pair| pair[0] == pair[1])))
+}
+
+pub fn list(args: Vec<Value>) -> Result<Value, Error> {
+    Ok(Value::list(args))
+}
+
+pub fn cons(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("cons", &args, 2)?;
+
+    let tail = args[1].as_list()?;
+
+    let mut items = Vec::with_capacity(tail.len() + 1);
+    items.push(args[0].clone());
+    items.extend(tail.iter().cloned());
+
+    Ok(Value::list(items))
+}
+
+/// `first` and `car`, `nil` for the empty list.
+pub fn first(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("first", &args, 1)?;
+
+    Ok(args[0].as_list()?.first().cloned().unwrap_or(Value::Nil))
+}
+
+/// `rest` and `cdr`
+pub fn rest(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("rest", &args, 1)?;
+
+    let list = args[0].as_list()?;
+
+    Ok(Value::list(list.iter().skip(1).cloned().collect()))
+}
+
+pub fn nth(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("nth", &args, 2)?;
+
+    let list = args[0].as_list()?;
+    let index = args[1].as_integer()?;
+
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| list.get(i))
+        .cloned()
+        .ok_or_else(|| {
+            Error::new(format!(
+                "index error: index {} is out of bounds for length {}",
+                index,
+                list.len()
+            ))
+        })
+}
+
+/// Strings are measured in characters.
+pub fn length(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("length", &args, 1)?;
+
+    if let Value::String(text) = &args[0] {
+        return Ok(Value::Integer(text.chars().count() as i64));
+    }
+
+    Ok(Value::Integer(args[0].as_list()?.len() as i64))
+}
+
+/// `empty?`
+pub fn is_empty(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("empty?", &args, 1)?;
+
+    Ok(Value::Bool(args[0].as_list()?.is_empty()))
+}
+
+pub fn append(args: Vec<Value>) -> Result<Value, Error> {
+    let mut items = Vec::new();
+
+    for arg in &args {
+        items.extend(arg.as_list()?.iter().cloned());
+    }
+
+    Ok(Value::list(items))
+}
+
+pub fn reverse(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("reverse", &args, 1)?;
+
+    Ok(Value::list(
+        args[0].as_list()?.iter().rev().cloned().collect(),
+    ))
+}
+
+/// `(map f coll...)` stops at the end of the shortest list.
+pub fn map(args: Vec<Value>) -> Result<Value, Error> {
+    expect_at_least("map", &args, 2)?;
+
+    let lists = args[1..]
+        .iter()
+        .map(Value::as_list)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let shortest = lists.iter().map(|list| list.len()).min().unwrap_or(0);
+
+    let results = (0..shortest)
+        .map(|index| args[0].call(lists.iter().map(|list| list[index].clone()).collect()))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Value::list(results))
+}
+
+pub fn filter(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("filter", &args, 2)?;
+
+    let mut results = Vec::new();
+
+    for item in args[1].as_list()?.iter() {
+        if args[0].call(vec![item.clone()])?.is_truthy() {
+            results.push(item.clone());
+        }
+    }
+
+    Ok(Value::list(results))
+}
+
+/// `reduce` and `fold`, `(reduce f coll)` seeds with the first element.
+pub fn reduce(args: Vec<Value>) -> Result<Value, Error> {
+    let (function, init, items) = match args.as_slice() {
+        [function, coll] => {
+            let list = coll.as_list()?;
+            let init = list.first().cloned().unwrap_or(Value::Nil);
+            (
+                function,
+                init,
+                list.iter().skip(1).cloned().collect::<Vec<_>>(),
+            )
+        }
+        [function, init, coll] => (function, init.clone(), coll.as_list()?.to_vec()),
+        _ => {
+            return Err(Error::new(format!(
+                "arity error: reduce expects 2 or 3 arguments, found {}",
+                args.len()
+            )))
+        }
+    };
+
+    items
+        .into_iter()
+        .try_fold(init, |acc, item| function.call(vec![acc, item]))
+}
+
+/// `(range end)`, `(range start end)` or `(range start end step)`, end exclusive.
+pub fn range(args: Vec<Value>) -> Result<Value, Error> {
+    let bounds = args
+        .iter()
+        .map(Value::as_integer)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (start, end, step) = match bounds.as_slice() {
+        [end] => (0, *end, 1),
+        [start, end] => (*start, *end, 1),
+        [start, end, step] => (*start, *end, *step),
+        _ => {
+            return Err(Error::new(format!(
+                "arity error: range expects 1 to 3 arguments, found {}",
+                args.len()
+            )))
+        }
+    };
+
+    if step == 0 {
+        return Err(Error::new("value error: range step must not be 0"));
+    }
+
+    let mut values = Vec::new();
+    let mut current = start;
+
+    while (step > 0 && current < end) || (step < 0 && current > end) {
+        values.push(Value::Integer(current));
+
+        // the range ends at the last value before the integers run out
+        match current.checked_add(step) {
+            Some(next) => current = next,
+            None => break,
+        }
+    }
+
+    Ok(Value::list(values))
+}
+
+/// Concatenates the plain text of every argument.
+pub fn str(args: Vec<Value>) -> Result<Value, Error> {
+    Ok(Value::String(
+        args.iter().map(Value::to_plain_string).collect(),
+    ))
+}
+
+fn map_string(name: &str, args: Vec<Value>, f: fn(&str) -> String) -> Result<Value, Error> {
+    expect_arity(name, &args, 1)?;
+
+    Ok(Value::String(f(args[0].as_str()?)))
+}
+
+pub fn upper(args: Vec<Value>) -> Result<Value, Error> {
+    map_string("upper", args, str::to_uppercase)
+}
+
+pub fn lower(args: Vec<Value>) -> Result<Value, Error> {
+    map_string("lower", args, str::to_lowercase)
+}
+
+pub fn trim(args: Vec<Value>) -> Result<Value, Error> {
+    map_string("trim", args, |text| text.trim().to_string())
+}
+
+/// `(split s sep)`, an empty separator splits into single characters.
+pub fn split(args: Vec<Value>) -> Result<Value, Error> {
+    expect_arity("split", &args, 2)?;
+
+    let text = args[0].as_str()?;
+    let separator = args[1].as_str()?;
+
+    let parts = if separator.is_empty() {
+        text.chars()
+            .map(|ch| Value::String(ch.to_string()))
+            .collect()
+    } else {
+        text.split(separator).map(Value::from).collect()
+    };
+
+    Ok(Value::list(parts))
+}
+
+/// `(join coll)` or `(join sep coll)`
+pub fn join(args: Vec<Value>) -> Result<Value, Error> {
+    let (separator, list) = match args.as_slice() {
+        [coll] => ("", coll.as_list()?),
+        [separator, coll] => (separator.as_str()?, coll.as_list()?),
+        _ => {
+            return Err(Error::new(format!(
+                "arity error: join expects 1 or 2 arguments, found {}",
+                args.len()
+            )))
+        }
+    };
+
+    Ok(Value::String(
+        list.iter()
+            .map(Value::to_plain_string)
+            .collect::<Vec<_>>()
+            .join(separator),
+    ))
+}
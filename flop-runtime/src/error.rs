@@ -0,0 +1,32 @@
+use crate::value::Value;
+use std::fmt;
+
+/// An error raised while a compiled program runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn type_error(expected: &str, found: &Value) -> Self {
+        Self::new(format!(
+            "type error: expected {}, found {}",
+            expected,
+            found.type_name()
+        ))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Error {}
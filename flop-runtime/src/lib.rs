@@ -0,0 +1,148 @@
+//! Support code for Rust programs generated by `flop-compiler`: the dynamic
+//! `Value` every Flop expression evaluates to, the builtin functions and global
+//! variables.
+
+mod builtins;
+mod error;
+mod value;
+
+pub use builtins::*;
+pub use error::Error;
+pub use value::{Function, Value};
+
+use std::{
+    cell::{Cell, RefCell},
+    process,
+    thread::LocalKey,
+};
+
+/// Stack size of the thread compiled programs run on, recursion that is not in
+/// tail position nests native calls.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+thread_local! {
+    /// Stack position where the program thread started, zero on other threads.
+    static STACK_BASE: Cell<usize> = const { Cell::new(0) };
+}
+
+/// What one step of a compiled function returns: its value, or the tail call of
+/// another function to make next, so mutual recursion doesn't nest native calls.
+pub enum Step {
+    Value(Value),
+    Call(Box<dyn FnOnce() -> Result<Step, Error>>),
+}
+
+/// Runs `step` and the tail calls it hands back until one returns a value.
+pub fn trampoline(step: Result<Step, Error>) -> Result<Value, Error> {
+    let mut step = step?;
+
+    loop {
+        match step {
+            Step::Value(value) => return Ok(value),
+            Step::Call(next) => step = next()?,
+        }
+    }
+}
+
+/// Fails once nested calls have used most of the program thread's stack, so deep
+/// recursion ends with an error instead of overflowing it.
+pub fn enter() -> Result<(), Error> {
+    let base = STACK_BASE.with(Cell::get);
+
+    // a quarter of the stack is left for the frames between two checks
+    if base != 0 && base.saturating_sub(stack_position()) > STACK_SIZE - STACK_SIZE / 4 {
+        return Err(Error::new("stack depth exceeded: too many nested calls"));
+    }
+
+    Ok(())
+}
+
+/// The address of a local in a frame of its own, the stack grows down from the
+/// program thread's first frame.
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0_u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+/// A global variable defined with `setq` at the top level, unbound until the first
+/// assignment.
+pub struct Global {
+    name: &'static str,
+    value: RefCell<Option<Value>>,
+}
+
+impl Global {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            value: RefCell::new(None),
+        }
+    }
+}
+
+/// The value of a global variable.
+pub fn get(global: &'static LocalKey<Global>) -> Result<Value, Error> {
+    global.with(|global| {
+        global
+            .value
+            .borrow()
+            .clone()
+            .ok_or_else(|| Error::new(format!("unbound variable: {}", global.name)))
+    })
+}
+
+/// Binds a global variable, replacing any previous value.
+pub fn set(global: &'static LocalKey<Global>, value: Value) {
+    global.with(|global| *global.value.borrow_mut() = Some(value));
+}
+
+/// Fails unless `function` was called with exactly `arity` arguments.
+pub fn expect_arity(function: &str, args: &[Value], arity: usize) -> Result<(), Error> {
+    if args.len() == arity {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "arity error: {} expects {} arguments, found {}",
+            function,
+            arity,
+            args.len()
+        )))
+    }
+}
+
+/// Fails unless `function` was called with at least `arity` arguments.
+pub fn expect_at_least(function: &str, args: &[Value], arity: usize) -> Result<(), Error> {
+    if args.len() >= arity {
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "arity error: {} expects at least {} arguments, found {}",
+            function,
+            arity,
+            args.len()
+        )))
+    }
+}
+
+/// Runs the body of a compiled program, reporting an error that escapes it and
+/// exiting with a failure status.
+pub fn run(program: fn() -> Result<(), Error>) {
+    let result = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            STACK_BASE.with(|base| base.set(stack_position()));
+            program()
+        })
+        .expect("Error starting the program thread")
+        .join();
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+        Err(_) => process::exit(101),
+    }
+}
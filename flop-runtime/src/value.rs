@@ -0,0 +1,167 @@
+use crate::error::Error;
+use std::{fmt, rc::Rc};
+
+/// A Flop value. Lists are shared, cloning a value never copies its elements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Keyword(String),
+    List(Rc<Vec<Value>>),
+    Function(Function),
+}
+
+/// A function passed around as a value, like a lambda or a `defn` handed to `map`.
+#[derive(Clone)]
+pub struct Function {
+    name: Rc<str>,
+    builtin: bool,
+    call: Rc<dyn Fn(Vec<Value>) -> Result<Value, Error>>,
+}
+
+impl Function {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn call(&self, args: Vec<Value>) -> Result<Value, Error> {
+        (self.call)(args)
+    }
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.builtin {
+            write!(f, "#<builtin {}>", self.name)
+        } else {
+            write!(f, "#<fn {}>", self.name)
+        }
+    }
+}
+
+/// Functions are only equal to themselves.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.call, &other.call)
+    }
+}
+
+impl Value {
+    pub fn list(items: Vec<Value>) -> Self {
+        Value::List(Rc::new(items))
+    }
+
+    pub fn keyword(name: &str) -> Self {
+        Value::Keyword(name.to_string())
+    }
+
+    pub fn function(
+        name: &str,
+        call: impl Fn(Vec<Value>) -> Result<Value, Error> + 'static,
+    ) -> Self {
+        Value::Function(Function {
+            name: name.into(),
+            builtin: false,
+            call: Rc::new(call),
+        })
+    }
+
+    /// A builtin function as a value, printed as a builtin.
+    pub fn builtin(name: &str, call: fn(Vec<Value>) -> Result<Value, Error>) -> Self {
+        Value::Function(Function {
+            name: name.into(),
+            builtin: true,
+            call: Rc::new(call),
+        })
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "NIL",
+            Value::Bool(_) => "BOOLEAN",
+            Value::Integer(_) => "INTEGER",
+            Value::Float(_) => "FLOAT",
+            Value::String(_) => "STRING",
+            Value::Keyword(_) => "KEYWORD",
+            Value::List(_) => "LIST",
+            Value::Function(_) => "FUNCTION",
+        }
+    }
+
+    /// Only `false` and `nil` are falsey.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+
+    /// Calls the value as a function.
+    pub fn call(&self, args: Vec<Value>) -> Result<Value, Error> {
+        match self {
+            Value::Function(function) => function.call(args),
+            other => Err(Error::type_error("FUNCTION", other)),
+        }
+    }
+
+    pub fn as_integer(&self) -> Result<i64, Error> {
+        match self {
+            Value::Integer(n) => Ok(*n),
+            other => Err(Error::type_error("INTEGER", other)),
+        }
+    }
+
+    /// `nil` is treated as the empty list.
+    pub fn as_list(&self) -> Result<Rc<Vec<Value>>, Error> {
+        match self {
+            Value::List(items) => Ok(items.clone()),
+            Value::Nil => Ok(Rc::default()),
+            other => Err(Error::type_error("LIST", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&str, Error> {
+        match self {
+            Value::String(text) => Ok(text),
+            other => Err(Error::type_error("STRING", other)),
+        }
+    }
+
+    /// The text of strings without quotes, every other value as it is printed.
+    pub fn to_plain_string(&self) -> String {
+        match self {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::String(text.to_string())
+    }
+}
+
+fn join(items: &[Value]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Prints values the way the interpreter does.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{:?}", n),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::Keyword(k) => write!(f, ":{}", k),
+            Value::List(items) => write!(f, "[{}]", join(items)),
+            Value::Function(function) => write!(f, "{:?}", function),
+        }
+    }
+}
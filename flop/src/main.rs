@@ -1,19 +1,134 @@
-use clap::Parser;
-use flop_interpretor::{file::Program, repl::Repl};
+use clap::{Parser, Subcommand};
+use flop_compiler::{native, project};
+use flop_interpretor::{env::DEFAULT_MAX_DEPTH, file::Program, repl::Repl};
 use miette::Result;
+use std::{
+    path::{Path, PathBuf},
+    thread,
+};
+
+/// Native stack of the thread files and the REPL are evaluated on, large enough
+/// for deep recursion without relying on the main thread's.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
 
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
 struct Opts {
-    #[arg(short, long, help = "Use read file mode")]
-    file: Option<String>,
+    #[arg(
+        short,
+        long = "file",
+        num_args = 1..,
+        help = "Use read file mode, evaluating the files in order into one environment"
+    )]
+    files: Vec<String>,
+
+    #[arg(long, help = "Maximum depth of nested non-tail calls", default_value_t = DEFAULT_MAX_DEPTH)]
+    max_depth: usize,
+
+    #[arg(
+        short = 'I',
+        long = "path",
+        help = "Directory to search for required modules"
+    )]
+    search_path: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Transpile a file to Rust, writing a Cargo project
+    Build {
+        file: PathBuf,
+
+        #[arg(short, long, help = "Directory of the generated project")]
+        output: Option<PathBuf>,
+
+        #[arg(long, help = "Path of the flop-runtime crate the project depends on")]
+        runtime: Option<PathBuf>,
+    },
+
+    /// Compile a file to a native executable with the local cargo
+    Compile {
+        file: PathBuf,
+
+        #[arg(short, long, help = "Path of the executable")]
+        output: Option<PathBuf>,
+
+        #[arg(long, help = "Directory to build the generated project in")]
+        build_dir: Option<PathBuf>,
+
+        #[arg(long, help = "Path of the flop-runtime crate the project depends on")]
+        runtime: Option<PathBuf>,
+    },
 }
 
 fn main() -> Result<()> {
     let opts = Opts::parse();
 
-    match &opts.file {
-        Some(file) => Program::new().run(file),
-        None => Repl::new().run(),
+    match &opts.command {
+        Some(Command::Build {
+            file,
+            output,
+            runtime,
+        }) => {
+            let output = output.clone().unwrap_or_else(|| default_output(file));
+            let main = project::build(file, &output, runtime.as_deref())?;
+
+            println!("wrote {}", main.display());
+            return Ok(());
+        }
+        Some(Command::Compile {
+            file,
+            output,
+            build_dir,
+            runtime,
+        }) => {
+            let output = output.clone().unwrap_or_else(|| default_output(file));
+            native::compile(file, &output, build_dir.as_deref(), runtime.as_deref())?;
+
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let evaluator = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || evaluate(opts))
+        .expect("cannot start the evaluation thread");
+
+    evaluator
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// Runs the files given, or the REPL when there are none.
+fn evaluate(opts: Opts) -> Result<()> {
+    if opts.files.is_empty() {
+        return Repl::new()
+            .with_max_depth(opts.max_depth)
+            .with_stack_size(STACK_SIZE)
+            .with_search_path(opts.search_path)
+            .run();
+    }
+
+    let mut program = Program::new()
+        .with_max_depth(opts.max_depth)
+        .with_stack_size(STACK_SIZE)
+        .with_search_path(opts.search_path);
+
+    for file in &opts.files {
+        program.run(file)?;
+    }
+
+    Ok(())
+}
+
+/// Named after the file, in the current directory.
+fn default_output(file: &Path) -> PathBuf {
+    match file.file_stem() {
+        Some(stem) => PathBuf::from(stem),
+        None => PathBuf::from("a.out"),
     }
 }
@@ -0,0 +1,111 @@
+//! Compiles the example scripts the compiler supports and checks the programs
+//! print the same `.out` the interpreter is held to, so the runtime builtins
+//! keep behaving like the interpreter's.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// Scripts using only the forms and builtins the compiler translates.
+const SCRIPTS: &[&str] = &[
+    "functions",
+    "lists",
+    "loops",
+    "math",
+    "ranges",
+    "recursion",
+    "variable",
+];
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+fn build() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("compiled")
+}
+
+/// Compiles `source` into an executable named after `name`, and runs it.
+fn compile_and_run(source: &Path, name: &str) -> Result<Output, String> {
+    let executable = build().join(format!("{}-bin", name));
+
+    let compiled = Command::new(env!("CARGO_BIN_EXE_flop"))
+        .env("NO_COLOR", "1")
+        .arg("compile")
+        .arg(source)
+        .arg("-o")
+        .arg(&executable)
+        .arg("--build-dir")
+        .arg(build().join(name))
+        .output()
+        .map_err(|err| format!("cannot run flop: {}", err))?;
+
+    if !compiled.status.success() {
+        return Err(format!(
+            "{} does not compile:\n{}",
+            name,
+            String::from_utf8_lossy(&compiled.stderr)
+        ));
+    }
+
+    Command::new(&executable)
+        .output()
+        .map_err(|err| format!("cannot run {}: {}", executable.display(), err))
+}
+
+fn check(script: &str) -> Result<(), String> {
+    let source = root().join("tests").join(format!("{}.ls", script));
+    let output = compile_and_run(&source, script)?;
+
+    let printed = String::from_utf8_lossy(&output.stdout);
+    let wanted = fs::read_to_string(source.with_extension("out")).map_err(|err| err.to_string())?;
+
+    if !output.status.success() || printed != wanted {
+        return Err(format!(
+            "{} compiled prints differently\n--- interpreted\n{}\n--- compiled\n{}{}",
+            script,
+            wanted,
+            printed,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compiled_scripts_print_what_the_interpreter_does() {
+    let failures = SCRIPTS
+        .iter()
+        .filter_map(|script| check(script).err())
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
+
+/// Mutual tail calls run in constant stack, and recursion too deep for the stack
+/// fails with an error rather than aborting the program.
+#[test]
+fn compiled_recursion_keeps_within_the_stack() {
+    let source = build().join("deep.ls");
+
+    fs::create_dir_all(build()).expect("build directory");
+    fs::write(
+        &source,
+        r#"(defn Even [n] "is n even" (if (== n 0) true (Odd (- n 1))))
+(defn Odd [n] "is n odd" (if (== n 0) false (Even (- n 1))))
+(Even 10000001)
+(defn Depth [n] "counts nested calls" (if (== n 0) 0 (+ 1 (Depth (- n 1)))))
+(Depth 100000000)
+"#,
+    )
+    .expect("write the script");
+
+    let output = compile_and_run(&source, "deep").unwrap_or_else(|failure| panic!("{}", failure));
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "false\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("stack depth exceeded"));
+}
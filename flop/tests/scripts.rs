@@ -0,0 +1,85 @@
+//! Runs the example scripts under `tests/` and compares what they print with the
+//! `.out` file next to each one. Set `FLOP_BLESS=1` to rewrite the expected
+//! output after an intended change.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+/// Runs `flop -f` with `files` and checks its standard output against `expected`.
+fn check(files: &[PathBuf], expected: &Path) -> Result<(), String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_flop"))
+        .current_dir(root())
+        .env("NO_COLOR", "1")
+        .arg("-f")
+        .args(files)
+        .output()
+        .map_err(|err| format!("cannot run flop: {}", err))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed:\n{}",
+            expected.display(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    if env::var_os("FLOP_BLESS").is_some() {
+        fs::write(expected, stdout.as_bytes()).map_err(|err| err.to_string())?;
+        return Ok(());
+    }
+
+    let wanted = fs::read_to_string(expected)
+        .map_err(|err| format!("cannot read {}: {}", expected.display(), err))?;
+
+    if stdout != wanted {
+        return Err(format!(
+            "{} differs\n--- expected\n{}\n--- printed\n{}",
+            expected.display(),
+            wanted,
+            stdout
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn scripts_print_their_expected_output() {
+    let mut scripts = fs::read_dir(root().join("tests"))
+        .expect("tests directory")
+        .map(|entry| entry.expect("directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ls"))
+        .collect::<Vec<_>>();
+
+    scripts.sort();
+
+    let failures = scripts
+        .iter()
+        .filter_map(|script| {
+            check(std::slice::from_ref(script), &script.with_extension("out")).err()
+        })
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}
+
+/// Files given together share one environment, a module run directly is not
+/// loaded again when a later file requires it.
+#[test]
+fn files_run_together_share_modules() {
+    let dir = root().join("tests").join("several");
+    let files = [dir.join("foo.ls"), dir.join("main.ls")];
+
+    if let Err(failure) = check(&files, &dir.join("main.out")) {
+        panic!("{}", failure);
+    }
+}